@@ -0,0 +1,208 @@
+/*
+ *   Copyright (c) 2022 Nazmul
+ *   All rights reserved.
+ *
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+ *
+ *   http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+ */
+
+//! Text containing right-to-left scripts (eg: Hebrew, Arabic) is stored in logical order (the
+//! order in which it is typed), but has to be painted in visual order (the order in which it
+//! appears on screen, left to right). [BidiLine] uses the Unicode Bidirectional Algorithm to
+//! compute the visual order of the grapheme clusters in a line, and maps cursor positions (display
+//! columns) between the two orders.
+//!
+//! Docs:
+//! - Unicode Bidirectional Algorithm (UAX #9): https://unicode.org/reports/tr9/
+//! - unicode-bidi crate: https://docs.rs/unicode-bidi/
+
+use unicode_bidi::BidiInfo;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// A grapheme cluster in a [BidiLine] along w/ where it is located in logical and visual order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BidiSegment {
+  pub string: String,
+  pub display_width: usize,
+  pub logical_display_col: usize,
+  pub visual_display_col: usize,
+  pub is_rtl: bool,
+}
+
+/// A single line of text (w/out newlines) w/ its grapheme clusters in logical order, and their
+/// visual order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BidiLine {
+  /// Grapheme clusters in logical order.
+  pub segments: Vec<BidiSegment>,
+  /// Indices into `segments`, in visual order.
+  pub visual_order: Vec<usize>,
+  pub display_width: usize,
+  /// The base direction of the line (eg: a line that starts w/ a Hebrew word is RTL, even if it
+  /// has some English words in it). The logical end of an RTL line is at its left edge.
+  pub is_rtl_paragraph: bool,
+}
+
+impl BidiLine {
+  pub fn new(text: &str) -> Self {
+    let bidi_info = BidiInfo::new(text, None);
+
+    // Grapheme clusters in logical order, w/ their starting byte offset.
+    let mut segments = vec![];
+    let mut byte_offsets = vec![];
+    let mut logical_display_col = 0;
+    for (byte_offset, g_c) in text.grapheme_indices(true) {
+      let display_width = UnicodeWidthStr::width(g_c);
+      segments.push(BidiSegment {
+        string: g_c.to_string(),
+        display_width,
+        logical_display_col,
+        visual_display_col: 0,
+        is_rtl: bidi_info.levels[byte_offset].is_rtl(),
+      });
+      byte_offsets.push(byte_offset);
+      logical_display_col += display_width;
+    }
+
+    // Each paragraph produces level runs that are already in visual order. Grapheme clusters
+    // inside an RTL run have to be reversed.
+    let mut visual_order = vec![];
+    for paragraph in &bidi_info.paragraphs {
+      let (_, runs) = bidi_info.visual_runs(paragraph, paragraph.range.clone());
+      for run in runs {
+        let mut indices_in_run = byte_offsets
+          .iter()
+          .enumerate()
+          .filter(|(_, byte_offset)| run.contains(byte_offset))
+          .map(|(index, _)| index)
+          .collect::<Vec<usize>>();
+        if bidi_info.levels[run.start].is_rtl() {
+          indices_in_run.reverse();
+        }
+        visual_order.extend(indices_in_run);
+      }
+    }
+
+    let mut visual_display_col = 0;
+    for index in &visual_order {
+      let segment = &mut segments[*index];
+      segment.visual_display_col = visual_display_col;
+      visual_display_col += segment.display_width;
+    }
+
+    let is_rtl_paragraph = bidi_info
+      .paragraphs
+      .first()
+      .is_some_and(|paragraph| paragraph.level.is_rtl());
+
+    Self {
+      segments,
+      visual_order,
+      display_width: logical_display_col,
+      is_rtl_paragraph,
+    }
+  }
+
+  pub fn has_rtl(&self) -> bool { self.segments.iter().any(|segment| segment.is_rtl) }
+
+  /// The string that should be painted on screen (left to right).
+  pub fn visual_string(&self) -> String {
+    self
+      .visual_order
+      .iter()
+      .map(|index| self.segments[*index].string.as_str())
+      .collect()
+  }
+
+  /// The visual display column of the grapheme cluster that occupies `logical_display_col`. A
+  /// column past the end of the line (eg: the cursor after the last typed character) maps to the
+  /// visual end of the line, which is the right edge of an LTR line, and the left edge (col 0) of
+  /// an RTL line.
+  pub fn logical_to_visual_col(&self, logical_display_col: usize) -> usize {
+    match self.segments.iter().find(|segment| {
+      (segment.logical_display_col..segment.logical_display_col + segment.display_width)
+        .contains(&logical_display_col)
+    }) {
+      Some(segment) => segment.visual_display_col,
+      None if self.is_rtl_paragraph => 0,
+      None => self.display_width,
+    }
+  }
+
+  /// The logical display column of the grapheme cluster that is painted at
+  /// `visual_display_col`. A column past the right edge of the line maps to the logical end of
+  /// an LTR line, and the logical start (col 0) of an RTL line.
+  pub fn visual_to_logical_col(&self, visual_display_col: usize) -> usize {
+    match self.segments.iter().find(|segment| {
+      (segment.visual_display_col..segment.visual_display_col + segment.display_width)
+        .contains(&visual_display_col)
+    }) {
+      Some(segment) => segment.logical_display_col,
+      None if self.is_rtl_paragraph => 0,
+      None => self.display_width,
+    }
+  }
+}
+
+#[test]
+fn test_ltr_line_is_unchanged() {
+  let line = BidiLine::new("Hi 📦 🙏🏽");
+  assert!(!line.has_rtl());
+  assert!(!line.is_rtl_paragraph);
+  assert_eq!(line.visual_string(), "Hi 📦 🙏🏽");
+  for segment in &line.segments {
+    assert_eq!(segment.logical_display_col, segment.visual_display_col);
+    assert_eq!(
+      line.logical_to_visual_col(segment.logical_display_col),
+      segment.logical_display_col
+    );
+  }
+}
+
+#[test]
+fn test_rtl_line_is_reversed() {
+  let line = BidiLine::new("שלום");
+  assert!(line.has_rtl());
+  assert_eq!(line.visual_string(), "םולש");
+  assert_eq!(line.logical_to_visual_col(0), 3);
+  assert_eq!(line.visual_to_logical_col(0), 3);
+  // The cursor after the last typed letter is at the left edge of an RTL line.
+  assert!(line.is_rtl_paragraph);
+  assert_eq!(line.logical_to_visual_col(4), 0);
+  assert_eq!(line.visual_to_logical_col(4), 0);
+}
+
+#[test]
+fn test_rtl_paragraph_with_ltr_run() {
+  // An RTL paragraph (it starts w/ a Hebrew name) w/ an English word in it.
+  let line = BidiLine::new("דנה abc");
+  assert!(line.is_rtl_paragraph);
+  assert_eq!(line.visual_string(), "abc הנד");
+  // The LTR run is painted on the left, and the end of the line is at col 0.
+  assert_eq!(line.logical_to_visual_col(4), 0);
+  assert_eq!(line.logical_to_visual_col(0), 6);
+  assert_eq!(line.logical_to_visual_col(7), 0);
+}
+
+#[test]
+fn test_mixed_ltr_and_rtl_runs() {
+  let line = BidiLine::new("abc אבג def");
+  assert!(!line.is_rtl_paragraph);
+  assert_eq!(line.visual_string(), "abc גבא def");
+  // The first RTL cluster 'א' is logically at col 4 and visually at col 6.
+  assert_eq!(line.logical_to_visual_col(4), 6);
+  assert_eq!(line.visual_to_logical_col(6), 4);
+  // LTR clusters keep their positions.
+  assert_eq!(line.logical_to_visual_col(0), 0);
+  assert_eq!(line.logical_to_visual_col(8), 8);
+}
@@ -15,5 +15,10 @@
  *   limitations under the License.
  */
 
+pub mod bidi;
+pub mod unicode_string;
 pub mod unicode_string_ext;
+
+pub use bidi::*;
+pub use unicode_string::*;
 pub use unicode_string_ext::*;
@@ -24,6 +24,7 @@
 //! - Grapheme clusters: https://medium.com/flutter-community/working-with-unicode-and-grapheme-clusters-in-dart-b054faab5705
 //! - UTF-8 String: https://doc.rust-lang.org/book/ch08-02-strings.html
 
+use graphemes::{BidiLine, UnicodeString};
 use seshat::unicode::{Segmentation, Ucd};
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
@@ -33,6 +34,8 @@ fn main() {
   print_cluster_breaks_using_seshat_and_unicode_width();
   print_graphemes_using_unicode_segmentation_and_unicode_width();
   print_grapheme_indices_using_unicode_segmentation_and_unicode_width();
+  print_bidi_visual_order();
+  print_unicode_string_segment_table();
 }
 
 pub fn print_graphemes() {
@@ -101,3 +104,42 @@ pub fn print_grapheme_indices_using_unicode_segmentation_and_unicode_width() {
   println! {"❯ s.chars().count(): {} ← UTF-8 chars (not grapheme clusters)", s.chars().count()};
   println! {"❯ s.len():           {} ← byte size", s.len()};
 }
+
+pub fn print_bidi_visual_order() {
+  println!("\n-- print_bidi_visual_order --\n");
+  let s = "Hi שלום 📦.";
+  let line = BidiLine::new(s);
+  println! {"❯ logical: {}", s};
+  println! {"❯ visual:  {}", line.visual_string()};
+  for segment in &line.segments {
+    println!(
+      r#"g_c = `{}` › logical_display_col = {:02} › visual_display_col = {:02} › is_rtl = {}"#,
+      segment.string, segment.logical_display_col, segment.visual_display_col, segment.is_rtl
+    );
+  }
+}
+
+pub fn print_unicode_string_segment_table() {
+  println!("\n-- print_unicode_string_segment_table --\n");
+  let s = "Hi 📦 🙏🏽 👨🏾‍🤝‍👨🏿.";
+  let u_s = UnicodeString::new(s);
+  for index in 0..u_s.len() {
+    let segment = u_s.segment(index).unwrap();
+    println!(
+      r#"index = {:02} › byte_offset = {:02} › col = {:02} › width = {:02} › g_c = `{}`"#,
+      index,
+      segment.byte_offset,
+      segment.display_col,
+      segment.display_width,
+      u_s.grapheme(index).unwrap()
+    );
+  }
+  let string_per_cluster_size = u_s
+    .graphemes()
+    .map(|g_c| std::mem::size_of::<String>() + g_c.len())
+    .sum::<usize>();
+  println!();
+  println! {"❯ segment table size:     {} bytes", u_s.segment_table_size_in_bytes()};
+  println! {"❯ compact segment table:  {}", u_s.is_compact()};
+  println! {"❯ String per cluster size: {} bytes", string_per_cluster_size};
+}
@@ -0,0 +1,520 @@
+/*
+ *   Copyright (c) 2022 Nazmul
+ *   All rights reserved.
+ *
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+ *
+ *   http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+ */
+
+//! [UnicodeString] keeps a single backing [String] plus a segment table w/ one entry per grapheme
+//! cluster (byte offset, display column, display width), instead of a [String] per cluster. Width
+//! and column queries don't need to re-segment the string, and looking up the cluster at a display
+//! column is a binary search.
+//!
+//! When the backing string (and its display width) is shorter than [u16::MAX], the segment table
+//! uses `u16` offsets and `u8` widths, so each entry takes 6 bytes rather than 24. This saves
+//! memory, but lookups take about as long as w/ the full table. Run
+//! `cargo test --release -- --ignored --nocapture bench_segment_table` to compare both tables w/
+//! re-segmenting the string on every query.
+//!
+//! [UnicodeString::edit] re-segments only the neighborhood of an edit and patches the segment
+//! table in place, rather than re-segmenting the whole string.
+
+use std::{mem::size_of, ops::Range};
+
+use unicode_segmentation::{GraphemeCursor, UnicodeSegmentation};
+
+use crate::AmbiguousWidthPolicy;
+
+/// A grapheme cluster in a [UnicodeString]'s backing string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Segment {
+  pub byte_offset: usize,
+  pub display_col: usize,
+  pub display_width: usize,
+}
+
+impl Segment {
+  pub fn display_col_range(&self) -> Range<usize> {
+    self.display_col..self.display_col + self.display_width
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct CompactSegment {
+  byte_offset: u16,
+  display_col: u16,
+  display_width: u8,
+}
+
+impl CompactSegment {
+  fn try_from_segment(segment: &Segment) -> Option<Self> {
+    Some(Self {
+      byte_offset: u16::try_from(segment.byte_offset).ok()?,
+      display_col: u16::try_from(segment.display_col).ok()?,
+      display_width: u8::try_from(segment.display_width).ok()?,
+    })
+  }
+
+  fn to_segment(self) -> Segment {
+    Segment {
+      byte_offset: self.byte_offset as usize,
+      display_col: self.display_col as usize,
+      display_width: self.display_width as usize,
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum SegmentTable {
+  Compact(Vec<CompactSegment>),
+  Full(Vec<Segment>),
+}
+
+impl SegmentTable {
+  /// Uses [SegmentTable::Compact] if every segment fits, and [SegmentTable::Full] otherwise.
+  fn from_segments(segments: Vec<Segment>) -> Self {
+    let compact_segments = segments
+      .iter()
+      .map(CompactSegment::try_from_segment)
+      .collect::<Option<Vec<CompactSegment>>>();
+    match compact_segments {
+      Some(compact_segments) => SegmentTable::Compact(compact_segments),
+      None => SegmentTable::Full(segments),
+    }
+  }
+
+  fn len(&self) -> usize {
+    match self {
+      SegmentTable::Compact(segments) => segments.len(),
+      SegmentTable::Full(segments) => segments.len(),
+    }
+  }
+
+  fn get(&self, index: usize) -> Option<Segment> {
+    match self {
+      SegmentTable::Compact(segments) => segments.get(index).map(|it| it.to_segment()),
+      SegmentTable::Full(segments) => segments.get(index).copied(),
+    }
+  }
+
+  fn size_in_bytes(&self) -> usize {
+    match self {
+      SegmentTable::Compact(segments) => segments.len() * size_of::<CompactSegment>(),
+      SegmentTable::Full(segments) => segments.len() * size_of::<Segment>(),
+    }
+  }
+
+  /// Replaces the segments in `range` w/ `new_segments`, and shifts the byte offsets and display
+  /// columns of the segments after `range` by `byte_delta` and `display_col_delta`. Switches to
+  /// [SegmentTable::Full] if the result no longer fits in [SegmentTable::Compact].
+  fn splice(
+    &mut self, range: Range<usize>, new_segments: Vec<Segment>, byte_delta: isize,
+    display_col_delta: isize,
+  ) {
+    let shift = |segment: Segment| Segment {
+      byte_offset: (segment.byte_offset as isize + byte_delta) as usize,
+      display_col: (segment.display_col as isize + display_col_delta) as usize,
+      display_width: segment.display_width,
+    };
+
+    if let SegmentTable::Compact(segments) = self {
+      // Offsets and columns only grow, so if the last segment fits then all of them do.
+      let compact_new_segments = new_segments
+        .iter()
+        .map(CompactSegment::try_from_segment)
+        .collect::<Option<Vec<CompactSegment>>>();
+      let last_segment_fits = match segments.last() {
+        Some(last) if range.end < segments.len() => {
+          CompactSegment::try_from_segment(&shift(last.to_segment())).is_some()
+        }
+        _ => true,
+      };
+      match (compact_new_segments, last_segment_fits) {
+        (Some(compact_new_segments), true) => {
+          let tail_start = range.start + compact_new_segments.len();
+          segments.splice(range, compact_new_segments);
+          for segment in &mut segments[tail_start..] {
+            *segment = CompactSegment::try_from_segment(&shift(segment.to_segment())).unwrap();
+          }
+          return;
+        }
+        _ => {
+          *self = SegmentTable::Full(segments.iter().map(|it| it.to_segment()).collect());
+        }
+      }
+    }
+
+    if let SegmentTable::Full(segments) = self {
+      let tail_start = range.start + new_segments.len();
+      segments.splice(range, new_segments);
+      for segment in &mut segments[tail_start..] {
+        *segment = shift(*segment);
+      }
+    }
+  }
+
+  /// Index of the segment whose display column range contains `display_col`.
+  fn find_by_display_col(&self, display_col: usize) -> Option<usize> {
+    let (mut low, mut high) = (0, self.len());
+    while low < high {
+      let mid = (low + high) / 2;
+      let segment = self.get(mid)?;
+      if display_col < segment.display_col {
+        high = mid;
+      } else if display_col >= segment.display_col + segment.display_width {
+        low = mid + 1;
+      } else {
+        return Some(mid);
+      }
+    }
+    None
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UnicodeString {
+  string: String,
+  segments: SegmentTable,
+  display_width: usize,
+  policy: AmbiguousWidthPolicy,
+}
+
+impl UnicodeString {
+  pub fn new(text: &str) -> Self {
+    UnicodeString::new_with_policy(text, AmbiguousWidthPolicy::default())
+  }
+
+  pub fn new_with_policy(text: &str, policy: AmbiguousWidthPolicy) -> Self {
+    let segments = segment(text, 0, 0, policy);
+    let display_width = segments.last().map_or(0, |it| it.display_col + it.display_width);
+    Self {
+      string: text.to_string(),
+      segments: SegmentTable::from_segments(segments),
+      display_width,
+      policy,
+    }
+  }
+
+  pub fn as_str(&self) -> &str { &self.string }
+
+  pub fn policy(&self) -> AmbiguousWidthPolicy { self.policy }
+
+  pub fn display_width(&self) -> usize { self.display_width }
+
+  /// Number of grapheme clusters.
+  pub fn len(&self) -> usize { self.segments.len() }
+
+  pub fn is_empty(&self) -> bool { self.segments.len() == 0 }
+
+  pub fn is_compact(&self) -> bool { matches!(self.segments, SegmentTable::Compact(_)) }
+
+  /// Memory used by the segment table (not including the backing string).
+  pub fn segment_table_size_in_bytes(&self) -> usize { self.segments.size_in_bytes() }
+
+  pub fn segment(&self, index: usize) -> Option<Segment> { self.segments.get(index) }
+
+  /// The grapheme cluster at `index`.
+  pub fn grapheme(&self, index: usize) -> Option<&str> {
+    let segment = self.segments.get(index)?;
+    let end_byte_offset = self.end_byte_offset_of(index);
+    Some(&self.string[segment.byte_offset..end_byte_offset])
+  }
+
+  pub fn graphemes(&self) -> impl Iterator<Item = &str> {
+    (0..self.len()).filter_map(move |index| self.grapheme(index))
+  }
+
+  /// Index of the grapheme cluster that occupies `display_col`.
+  pub fn index_at_display_col(&self, display_col: usize) -> Option<usize> {
+    self.segments.find_by_display_col(display_col)
+  }
+
+  /// The longest prefix (w/out splitting any clusters) that fits in `max_display_width`.
+  pub fn truncate_to_display_width(&self, max_display_width: usize) -> &str {
+    if max_display_width >= self.display_width {
+      return &self.string;
+    }
+    match self.segments.find_by_display_col(max_display_width) {
+      Some(index) => &self.string[..self.segments.get(index).unwrap().byte_offset],
+      None => &self.string,
+    }
+  }
+
+  /// Replaces the grapheme clusters in `range` (cluster indices, not byte offsets) w/
+  /// `replacement`. Only the clusters around the edit are re-segmented, since the clusters before
+  /// the cluster preceding the edit can't change. Clusters after the edit are re-segmented until a
+  /// boundary that existed before the edit is found again (eg: inserting a regional indicator can
+  /// re-pair all the flags that follow it).
+  ///
+  /// Panics if `range` is out of bounds.
+  pub fn edit(&mut self, range: Range<usize>, replacement: &str) {
+    assert!(range.start <= range.end && range.end <= self.len(), "range out of bounds");
+    let old_len = self.len();
+
+    let start_byte_offset = self.start_byte_offset_of(range.start);
+    let end_byte_offset = self.start_byte_offset_of(range.end);
+    let byte_delta = replacement.len() as isize - (end_byte_offset - start_byte_offset) as isize;
+    self.string.replace_range(start_byte_offset..end_byte_offset, replacement);
+
+    // Start re-segmenting from the cluster right before the edit, since it can merge w/ the
+    // replacement (eg: when a combining mark or ZWJ sequence is inserted).
+    let left_index = range.start.saturating_sub(1);
+    let (left_byte_offset, left_display_col) = match self.segments.get(left_index) {
+      Some(segment) => (segment.byte_offset, segment.display_col),
+      None => (0, 0),
+    };
+
+    // Find the first old boundary at or after the end of the edit that is still a boundary.
+    let mut right_index = range.end;
+    let right_byte_offset = loop {
+      if right_index == old_len {
+        break self.string.len();
+      }
+      let candidate = (self.segments.get(right_index).unwrap().byte_offset as isize
+        + byte_delta) as usize;
+      let mut cursor = GraphemeCursor::new(candidate, self.string.len(), true);
+      if matches!(cursor.is_boundary(&self.string, 0), Ok(true)) {
+        break candidate;
+      }
+      right_index += 1;
+    };
+
+    let new_segments = segment(
+      &self.string[left_byte_offset..right_byte_offset],
+      left_byte_offset,
+      left_display_col,
+      self.policy,
+    );
+    let old_right_display_col = match self.segments.get(right_index) {
+      Some(segment) => segment.display_col,
+      None => self.display_width,
+    };
+    let new_right_display_col =
+      new_segments.last().map_or(left_display_col, |it| it.display_col + it.display_width);
+    let display_col_delta = new_right_display_col as isize - old_right_display_col as isize;
+
+    self.segments.splice(
+      left_index.min(old_len)..right_index,
+      new_segments,
+      byte_delta,
+      display_col_delta,
+    );
+    self.display_width = (self.display_width as isize + display_col_delta) as usize;
+  }
+
+  /// Byte offset of the cluster at `index`, or the length of the backing string if `index` is
+  /// the number of clusters.
+  fn start_byte_offset_of(&self, index: usize) -> usize {
+    match self.segments.get(index) {
+      Some(segment) => segment.byte_offset,
+      None => self.string.len(),
+    }
+  }
+
+  fn end_byte_offset_of(&self, index: usize) -> usize {
+    match self.segments.get(index + 1) {
+      Some(next_segment) => next_segment.byte_offset,
+      None => self.string.len(),
+    }
+  }
+}
+
+/// Segments `text`, where the first cluster starts at `start_byte_offset` and
+/// `start_display_col` of the backing string.
+fn segment(
+  text: &str, start_byte_offset: usize, start_display_col: usize, policy: AmbiguousWidthPolicy,
+) -> Vec<Segment> {
+  let mut display_col = start_display_col;
+  text
+    .grapheme_indices(true)
+    .map(|(byte_offset, g_c)| {
+      let display_width = policy.width(g_c);
+      let segment = Segment {
+        byte_offset: start_byte_offset + byte_offset,
+        display_col,
+        display_width,
+      };
+      display_col += display_width;
+      segment
+    })
+    .collect()
+}
+
+#[test]
+fn test_unicode_string_matches_graphemes() {
+  let text = "Hi 📦 🙏🏽 👨🏾‍🤝‍👨🏿.";
+  let u_s = UnicodeString::new(text);
+  assert!(u_s.is_compact());
+  assert_eq!(u_s.as_str(), text);
+  assert_eq!(
+    u_s.graphemes().collect::<Vec<&str>>(),
+    text.graphemes(true).collect::<Vec<&str>>()
+  );
+  assert_eq!(u_s.display_width(), unicode_width::UnicodeWidthStr::width(text));
+  assert_eq!(u_s.len(), text.graphemes(true).count());
+}
+
+#[test]
+fn test_unicode_string_display_col_queries() {
+  let u_s = UnicodeString::new("a📦b");
+  assert_eq!(u_s.index_at_display_col(0), Some(0));
+  assert_eq!(u_s.index_at_display_col(1), Some(1));
+  assert_eq!(u_s.index_at_display_col(2), Some(1));
+  assert_eq!(u_s.index_at_display_col(3), Some(2));
+  assert_eq!(u_s.index_at_display_col(4), None);
+  assert_eq!(u_s.truncate_to_display_width(2), "a");
+  assert_eq!(u_s.truncate_to_display_width(3), "a📦");
+  assert_eq!(u_s.truncate_to_display_width(10), "a📦b");
+}
+
+#[test]
+fn test_unicode_string_uses_full_table_for_long_strings() {
+  let text = "📦".repeat(u16::MAX as usize);
+  let u_s = UnicodeString::new(&text);
+  assert!(!u_s.is_compact());
+  assert_eq!(u_s.len(), u16::MAX as usize);
+  assert_eq!(u_s.grapheme(u_s.len() - 1), Some("📦"));
+  assert_eq!(u_s.display_width(), 2 * u16::MAX as usize);
+}
+
+#[test]
+fn test_unicode_string_respects_policy() {
+  let u_s = UnicodeString::new_with_policy("±1", AmbiguousWidthPolicy::Wide);
+  assert_eq!(u_s.display_width(), 3);
+  assert_eq!(u_s.index_at_display_col(1), Some(0));
+}
+
+/// Applies the edit and checks that the result is the same as segmenting the edited string from
+/// scratch.
+#[cfg(test)]
+fn assert_edit_matches_full_resegmentation(text: &str, range: Range<usize>, replacement: &str) {
+  let mut u_s = UnicodeString::new(text);
+  u_s.edit(range, replacement);
+  let expected = UnicodeString::new(u_s.as_str());
+  let segments_of =
+    |it: &UnicodeString| (0..it.len()).map(|index| it.segment(index)).collect::<Vec<_>>();
+  assert_eq!(segments_of(&u_s), segments_of(&expected), "edited: {:?}", u_s.as_str());
+  assert_eq!(u_s.display_width(), expected.display_width());
+}
+
+#[test]
+fn test_edit_insert_delete_replace() {
+  assert_edit_matches_full_resegmentation("", 0..0, "Hi 📦");
+  assert_edit_matches_full_resegmentation("Hi 📦", 0..0, "👋 ");
+  assert_edit_matches_full_resegmentation("Hi 📦", 3..4, "");
+  assert_edit_matches_full_resegmentation("Hi 📦 🙏🏽 👨🏾‍🤝‍👨🏿.", 5..6, "日本");
+  assert_edit_matches_full_resegmentation("Hi 📦 🙏🏽 👨🏾‍🤝‍👨🏿.", 0..9, "");
+
+  let mut u_s = UnicodeString::new("a📦b");
+  u_s.edit(1..2, "c");
+  assert_eq!(u_s.as_str(), "acb");
+  assert_eq!(u_s.display_width(), 3);
+}
+
+#[test]
+fn test_edit_merges_with_neighbors() {
+  // Combining acute accent merges w/ the preceding `e`.
+  assert_edit_matches_full_resegmentation("cafe!", 4..4, "\u{301}");
+  // ZWJ sequence merges w/ the preceding emoji.
+  assert_edit_matches_full_resegmentation("a👨b", 2..2, "\u{200D}👩");
+  // Deleting the `b` between two emoji doesn't join them (no ZWJ).
+  assert_edit_matches_full_resegmentation("👨b👩", 1..2, "");
+}
+
+#[test]
+fn test_edit_re_pairs_regional_indicators() {
+  // Inserting a lone regional indicator re-pairs all the flags that follow it.
+  assert_edit_matches_full_resegmentation("🇺🇸🇬🇧🇫🇷", 0..0, "\u{1F1FA}");
+  assert_edit_matches_full_resegmentation("x🇺🇸🇬🇧🇫🇷y", 1..2, "\u{1F1FA}");
+}
+
+#[test]
+fn test_edit_switches_to_full_table_when_needed() {
+  let text = "a".repeat(u16::MAX as usize - 10);
+  let mut u_s = UnicodeString::new(&text);
+  assert!(u_s.is_compact());
+  let len = u_s.len();
+  u_s.edit(len..len, &"📦".repeat(10));
+  assert!(!u_s.is_compact());
+  assert_eq!(u_s.grapheme(u_s.len() - 1), Some("📦"));
+  assert_eq!(u_s.display_width(), u16::MAX as usize - 10 + 20);
+}
+
+/// Timing comparison of display width & display column queries on a [SegmentTable::Compact] vs a
+/// [SegmentTable::Full] table, w/ re-segmenting the string on every query (which is what
+/// [crate::UnicodeStringExt] does) as the baseline. Run w/:
+/// `cargo test --release -- --ignored --nocapture bench_segment_table`
+#[test]
+#[ignore]
+fn bench_segment_table_compact_vs_full() {
+  use std::time::{Duration, Instant};
+
+  use crate::UnicodeStringExt;
+
+  let text = "Hi 📦 🙏🏽 👨🏾‍🤝‍👨🏿 ±1. ".repeat(1_000);
+  let policy = AmbiguousWidthPolicy::Narrow;
+  let compact = UnicodeString::new(&text);
+  assert!(compact.is_compact());
+  let mut full = compact.clone();
+  full.segments =
+    SegmentTable::Full((0..full.len()).map(|index| full.segment(index).unwrap()).collect());
+  let display_width = compact.display_width();
+
+  // Returns the time per query, and a checksum of the results.
+  let time = |queries: usize, query: &dyn Fn(usize) -> usize| -> (Duration, usize) {
+    let start = Instant::now();
+    let checksum = (0..queries)
+      .map(|index| query(index * 7919 % display_width))
+      .fold(0, usize::wrapping_add);
+    (start.elapsed() / queries as u32, checksum)
+  };
+
+  let (compact_col, compact_col_checksum) =
+    time(1_000_000, &|col| compact.index_at_display_col(col).unwrap_or(0));
+  let (full_col, full_col_checksum) =
+    time(1_000_000, &|col| full.index_at_display_col(col).unwrap_or(0));
+  let (baseline_col, baseline_col_checksum) = time(100, &|col| {
+    let mut width = 0;
+    for (index, g_c) in text.graphemes(true).enumerate() {
+      width += policy.width(g_c);
+      if width > col {
+        return index;
+      }
+    }
+    0
+  });
+  assert_eq!(compact_col_checksum, full_col_checksum);
+  assert_eq!(
+    time(100, &|col| compact.index_at_display_col(col).unwrap_or(0)).1,
+    baseline_col_checksum
+  );
+
+  let (compact_width, _) = time(1_000_000, &|_| compact.display_width());
+  let (full_width, _) = time(1_000_000, &|_| full.display_width());
+  let (baseline_width, _) = time(100, &|_| text.display_width(policy));
+
+  println!("clusters: {}, display width: {}", compact.len(), display_width);
+  println!(
+    "segment table size: compact = {} bytes, full = {} bytes",
+    compact.segment_table_size_in_bytes(),
+    full.segment_table_size_in_bytes()
+  );
+  println!(
+    "index_at_display_col: compact = {:?}, full = {:?}, re-segmenting = {:?}",
+    compact_col, full_col, baseline_col
+  );
+  println!(
+    "display_width:        compact = {:?}, full = {:?}, re-segmenting = {:?}",
+    compact_width, full_width, baseline_width
+  );
+}
@@ -15,10 +15,162 @@
  *   limitations under the License.
  */
 
+//! East Asian Ambiguous characters (eg: `°`, `±`, `…`, Greek and Cyrillic letters) are 1 column
+//! wide in most terminals, but 2 columns wide in terminals configured for CJK locales. All the
+//! width, truncation, and wrapping functions take an [AmbiguousWidthPolicy] so that callers that
+//! lay out text (in columns) agree w/ the terminal.
+//!
+//! Docs:
+//! - East Asian Width (UAX #11): https://www.unicode.org/reports/tr11/
+
+use std::env;
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Environment variable that overrides the detected policy. Values: `narrow` or `wide`.
+pub const AMBIGUOUS_WIDTH_ENV_VAR: &str = "AMBIGUOUS_WIDTH";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AmbiguousWidthPolicy {
+  /// Ambiguous characters are 1 column wide.
+  #[default]
+  Narrow,
+  /// Ambiguous characters are 2 columns wide.
+  Wide,
+}
+
+impl AmbiguousWidthPolicy {
+  /// Uses the `AMBIGUOUS_WIDTH` environment variable if it is set, otherwise the terminal's
+  /// locale (`LC_ALL`, `LC_CTYPE`, `LANG`).
+  pub fn detect() -> Self {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+      .iter()
+      .find_map(|name| env::var(name).ok().filter(|value| !value.is_empty()));
+    AmbiguousWidthPolicy::from_env_vars(env::var(AMBIGUOUS_WIDTH_ENV_VAR).ok(), locale)
+  }
+
+  /// Chinese, Japanese, and Korean locales are treated as [AmbiguousWidthPolicy::Wide]. The
+  /// language is the part of the locale before any `_`, `.`, or `@` (eg: `ko` in
+  /// `ko_KR.UTF-8`), so `kok_IN` (Konkani) is not Korean.
+  pub fn from_env_vars(ambiguous_width: Option<String>, locale: Option<String>) -> Self {
+    match ambiguous_width.as_deref().map(str::to_lowercase).as_deref() {
+      Some("wide") => return AmbiguousWidthPolicy::Wide,
+      Some("narrow") => return AmbiguousWidthPolicy::Narrow,
+      _ => {}
+    }
+    let language = locale
+      .as_deref()
+      .and_then(|locale| locale.split(['_', '.', '@']).next());
+    match language {
+      Some("zh" | "ja" | "ko") => AmbiguousWidthPolicy::Wide,
+      _ => AmbiguousWidthPolicy::Narrow,
+    }
+  }
+
+  pub fn width(&self, text: &str) -> usize {
+    match self {
+      AmbiguousWidthPolicy::Narrow => UnicodeWidthStr::width(text),
+      AmbiguousWidthPolicy::Wide => UnicodeWidthStr::width_cjk(text),
+    }
+  }
+}
+
 pub trait UnicodeStringExt {
+  fn display_width(&self, policy: AmbiguousWidthPolicy) -> usize;
 
+  /// Drops grapheme clusters from the end so that the result fits in `max_display_width`. A
+  /// cluster is never split.
+  fn truncate_to_display_width(
+    &self, max_display_width: usize, policy: AmbiguousWidthPolicy,
+  ) -> String;
+
+  /// Splits into lines that each fit in `max_display_width`. A cluster that is wider than
+  /// `max_display_width` is placed on a line by itself.
+  fn wrap_to_display_width(
+    &self, max_display_width: usize, policy: AmbiguousWidthPolicy,
+  ) -> Vec<String>;
 }
 
 impl UnicodeStringExt for String {
-  
+  fn display_width(&self, policy: AmbiguousWidthPolicy) -> usize { policy.width(self) }
+
+  fn truncate_to_display_width(
+    &self, max_display_width: usize, policy: AmbiguousWidthPolicy,
+  ) -> String {
+    let mut truncated = String::new();
+    let mut display_width = 0;
+    for g_c in self.graphemes(true) {
+      let g_c_display_width = policy.width(g_c);
+      if display_width + g_c_display_width > max_display_width {
+        break;
+      }
+      truncated.push_str(g_c);
+      display_width += g_c_display_width;
+    }
+    truncated
+  }
+
+  fn wrap_to_display_width(
+    &self, max_display_width: usize, policy: AmbiguousWidthPolicy,
+  ) -> Vec<String> {
+    let mut lines = vec![];
+    let mut line = String::new();
+    let mut display_width = 0;
+    for g_c in self.graphemes(true) {
+      let g_c_display_width = policy.width(g_c);
+      if display_width + g_c_display_width > max_display_width && !line.is_empty() {
+        lines.push(std::mem::take(&mut line));
+        display_width = 0;
+      }
+      line.push_str(g_c);
+      display_width += g_c_display_width;
+    }
+    if !line.is_empty() {
+      lines.push(line);
+    }
+    lines
+  }
+}
+
+#[test]
+fn test_ambiguous_width_policy_from_env_vars() {
+  use AmbiguousWidthPolicy::*;
+  assert_eq!(AmbiguousWidthPolicy::from_env_vars(None, None), Narrow);
+  assert_eq!(AmbiguousWidthPolicy::from_env_vars(None, Some("en_US.UTF-8".into())), Narrow);
+  assert_eq!(AmbiguousWidthPolicy::from_env_vars(None, Some("ja_JP.UTF-8".into())), Wide);
+  assert_eq!(AmbiguousWidthPolicy::from_env_vars(None, Some("ko".into())), Wide);
+  assert_eq!(AmbiguousWidthPolicy::from_env_vars(None, Some("zh@stroke".into())), Wide);
+  // Konkani starts w/ `ko`, but isn't Korean.
+  assert_eq!(AmbiguousWidthPolicy::from_env_vars(None, Some("kok_IN.UTF-8".into())), Narrow);
+  assert_eq!(
+    AmbiguousWidthPolicy::from_env_vars(Some("narrow".into()), Some("zh_CN.UTF-8".into())),
+    Narrow
+  );
+  assert_eq!(AmbiguousWidthPolicy::from_env_vars(Some("WIDE".into()), None), Wide);
+}
+
+#[test]
+fn test_display_width_depends_on_policy() {
+  let text = "20°C ±1".to_string();
+  assert_eq!(text.display_width(AmbiguousWidthPolicy::Narrow), 7);
+  assert_eq!(text.display_width(AmbiguousWidthPolicy::Wide), 9);
+  // Wide (non ambiguous) characters are not affected by the policy.
+  let text = "📦 日本".to_string();
+  assert_eq!(text.display_width(AmbiguousWidthPolicy::Narrow), 7);
+  assert_eq!(text.display_width(AmbiguousWidthPolicy::Wide), 7);
+}
+
+#[test]
+fn test_truncate_and_wrap_use_policy() {
+  let text = "°°°°".to_string();
+  assert_eq!(text.truncate_to_display_width(3, AmbiguousWidthPolicy::Narrow), "°°°");
+  assert_eq!(text.truncate_to_display_width(3, AmbiguousWidthPolicy::Wide), "°");
+  assert_eq!(text.wrap_to_display_width(2, AmbiguousWidthPolicy::Narrow), vec!["°°", "°°"]);
+  assert_eq!(
+    text.wrap_to_display_width(2, AmbiguousWidthPolicy::Wide),
+    vec!["°", "°", "°", "°"]
+  );
+  // A cluster wider than the max width is not split or dropped when wrapping.
+  assert_eq!("📦".to_string().wrap_to_display_width(1, AmbiguousWidthPolicy::Narrow), vec!["📦"]);
 }
@@ -27,6 +27,7 @@ mod builder;
 mod utils;
 mod logger;
 mod custom_syntax;
+mod ts_type;
 
 #[proc_macro]
 pub fn fn_macro_ast_viz_debug(input: TokenStream) -> TokenStream {
@@ -48,6 +49,11 @@ pub fn derive_macro_builder(input: TokenStream) -> TokenStream {
   builder::derive_proc_macro_impl(input)
 }
 
+#[proc_macro_derive(TsType)]
+pub fn derive_macro_ts_type(input: TokenStream) -> TokenStream {
+  ts_type::derive_proc_macro_impl(input)
+}
+
 #[proc_macro_attribute]
 pub fn attrib_macro_logger_1(
   args: TokenStream,
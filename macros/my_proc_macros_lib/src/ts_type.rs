@@ -0,0 +1,299 @@
+/*
+ *   Copyright (c) 2022 Nazmul Idris
+ *   All rights reserved.
+
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+
+ *   http://www.apache.org/licenses/LICENSE-2.0
+
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+*/
+
+//! Generates a TypeScript type definition (the contents of a `.d.ts` file) for a struct or
+//! enum, so the same shapes can be used by a TS frontend that talks to the Rust code via
+//! JSON. The definition matches how `serde_json` serializes these types by default:
+//! 1. Structs w/ named fields become an `interface`, tuple structs become a tuple (or the
+//!    inner type if there's only one field), and unit structs become `null`.
+//! 2. Enums are externally tagged, so each variant becomes one of: `"Unit"`, `{ Tuple:
+//!    [number, string] }`, or `{ Struct: { a: number; } }`.
+//! 3. `Option<T>` becomes `T | null`, `Vec<T>` becomes `T[]`, `HashMap<K, V>` becomes
+//!    `Record<K, V>`, and any other type is referred to by its name (so it should derive
+//!    `TsType` too).
+//!
+//! The definition is returned by the generated `ts_type_definition()` fn. It is also
+//! written at compile time to `<TypeName>.d.ts` in the directory in the
+//! [TS_TYPE_OUT_DIR_ENV_VAR] env var, or `OUT_DIR` (which cargo sets for crates that have a
+//! build script). If neither is set, then no file is written.
+//!
+//! serde JSON enum representations: <https://serde.rs/enum-representations.html>
+
+use std::{env, fs, io, path::PathBuf};
+
+use quote::quote;
+use syn::{parse_macro_input,
+          Data::{Enum, Struct, Union},
+          DataEnum,
+          DeriveInput,
+          Fields::{self, Named, Unit, Unnamed},
+          FieldsNamed,
+          FieldsUnnamed,
+          GenericArgument,
+          Generics,
+          Ident,
+          PathArguments,
+          Type};
+
+const TS_TYPE_OUT_DIR_ENV_VAR: &str = "TS_TYPE_OUT_DIR";
+
+const NUMBER_TYPES: [&str; 14] = [
+  "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+  "f32", "f64",
+];
+
+pub fn derive_proc_macro_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+  let DeriveInput {
+    ident: type_name_ident,
+    data,
+    generics,
+    ..
+  } = parse_macro_input!(input as DeriveInput);
+
+  let ts_type_name = gen_ts_type_name(&type_name_ident, &generics);
+
+  let ts_type_definition = match data {
+    Struct(my_struct) => gen_ts_type_for_struct(&ts_type_name, &my_struct.fields),
+    Enum(my_enum) => gen_ts_type_for_enum(&ts_type_name, &my_enum),
+    Union(_) => {
+      return syn::Error::new(
+        type_name_ident.span(),
+        "TsType can't be derived for unions",
+      )
+      .to_compile_error()
+      .into();
+    }
+  };
+
+  if let Err(err) = write_ts_type_definition_file(&type_name_ident, &ts_type_definition) {
+    return syn::Error::new(
+      type_name_ident.span(),
+      format!("Could not write the TypeScript type definition: {}", err),
+    )
+    .to_compile_error()
+    .into();
+  }
+
+  let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+  quote! {
+    impl #impl_generics #type_name_ident #ty_generics #where_clause {
+      pub fn ts_type_definition() -> &'static str {
+        #ts_type_definition
+      }
+    }
+  }
+  .into()
+}
+
+/// Eg: `Point<X, Y>`. Lifetimes & const generics are dropped.
+fn gen_ts_type_name(
+  type_name_ident: &Ident,
+  generics: &Generics,
+) -> String {
+  let type_params = generics
+    .type_params()
+    .map(|type_param| type_param.ident.to_string())
+    .collect::<Vec<String>>();
+  match type_params.is_empty() {
+    true => type_name_ident.to_string(),
+    false => format!("{}<{}>", type_name_ident, type_params.join(", ")),
+  }
+}
+
+fn gen_ts_type_for_struct(
+  ts_type_name: &str,
+  fields: &Fields,
+) -> String {
+  match fields {
+    Named(fields) => {
+      let props = fields
+        .named
+        .iter()
+        .map(|field| format!("  {};\n", gen_ts_prop(field)))
+        .collect::<String>();
+      format!("export interface {} {{\n{}}}\n", ts_type_name, props)
+    }
+    Unnamed(fields) => format!(
+      "export type {} = {};\n",
+      ts_type_name,
+      gen_ts_type_for_unnamed_fields(fields)
+    ),
+    Unit => format!("export type {} = null;\n", ts_type_name),
+  }
+}
+
+fn gen_ts_type_for_enum(
+  ts_type_name: &str,
+  my_enum: &DataEnum,
+) -> String {
+  let variants = my_enum
+    .variants
+    .iter()
+    .map(|variant| match &variant.fields {
+      Unit => format!("\"{}\"", variant.ident),
+      Unnamed(fields) => format!(
+        "{{ {}: {} }}",
+        variant.ident,
+        gen_ts_type_for_unnamed_fields(fields)
+      ),
+      Named(fields) => format!(
+        "{{ {}: {} }}",
+        variant.ident,
+        gen_ts_type_for_named_fields_inline(fields)
+      ),
+    })
+    .collect::<Vec<String>>();
+
+  match variants.is_empty() {
+    true => format!("export type {} = never;\n", ts_type_name),
+    false => format!(
+      "export type {} =\n  | {};\n",
+      ts_type_name,
+      variants.join("\n  | ")
+    ),
+  }
+}
+
+/// Eg: `name: string`.
+fn gen_ts_prop(field: &syn::Field) -> String {
+  let field_name = field
+    .ident
+    .as_ref()
+    .unwrap()
+    .to_string();
+  format!(
+    "{}: {}",
+    field_name.trim_start_matches("r#"),
+    to_ts_type(&field.ty)
+  )
+}
+
+/// Eg: `{ x: number; y: number; }`.
+fn gen_ts_type_for_named_fields_inline(fields: &FieldsNamed) -> String {
+  let props = fields
+    .named
+    .iter()
+    .map(|field| format!("{}; ", gen_ts_prop(field)))
+    .collect::<String>();
+  format!("{{ {}}}", props)
+}
+
+/// A single field is unwrapped (newtype), otherwise it's a tuple, eg: `[number, string]`.
+fn gen_ts_type_for_unnamed_fields(fields: &FieldsUnnamed) -> String {
+  let ts_types = fields
+    .unnamed
+    .iter()
+    .map(|field| to_ts_type(&field.ty))
+    .collect::<Vec<String>>();
+  match ts_types.len() {
+    1 => ts_types[0].clone(),
+    _ => format!("[{}]", ts_types.join(", ")),
+  }
+}
+
+fn to_ts_type(ty: &Type) -> String {
+  match ty {
+    Type::Path(type_path) => {
+      let segment = type_path
+        .path
+        .segments
+        .last()
+        .unwrap();
+      let type_name = segment.ident.to_string();
+      let generic_args = get_generic_type_args(&segment.arguments);
+      match (type_name.as_str(), generic_args.as_slice()) {
+        (name, []) if NUMBER_TYPES.contains(&name) => "number".to_string(),
+        ("String" | "str" | "char", []) => "string".to_string(),
+        ("bool", []) => "boolean".to_string(),
+        ("Option", [inner]) => format!("{} | null", to_ts_type(inner)),
+        ("Vec" | "VecDeque" | "HashSet" | "BTreeSet", [inner]) => to_ts_array_type(inner),
+        ("HashMap" | "BTreeMap", [key, value]) => {
+          format!("Record<{}, {}>", to_ts_type(key), to_ts_type(value))
+        }
+        ("Box" | "Rc" | "Arc", [inner]) => to_ts_type(inner),
+        (name, []) => name.to_string(),
+        (name, args) => format!(
+          "{}<{}>",
+          name,
+          args
+            .iter()
+            .map(|arg| to_ts_type(arg))
+            .collect::<Vec<String>>()
+            .join(", ")
+        ),
+      }
+    }
+    Type::Reference(type_reference) => to_ts_type(&type_reference.elem),
+    Type::Slice(type_slice) => to_ts_array_type(&type_slice.elem),
+    Type::Array(type_array) => to_ts_array_type(&type_array.elem),
+    Type::Tuple(type_tuple) if type_tuple.elems.is_empty() => "null".to_string(),
+    Type::Tuple(type_tuple) => format!(
+      "[{}]",
+      type_tuple
+        .elems
+        .iter()
+        .map(to_ts_type)
+        .collect::<Vec<String>>()
+        .join(", ")
+    ),
+    Type::Paren(type_paren) => to_ts_type(&type_paren.elem),
+    Type::Group(type_group) => to_ts_type(&type_group.elem),
+    _ => "unknown".to_string(),
+  }
+}
+
+/// Eg: `number[]`, or `(number | null)[]`.
+fn to_ts_array_type(inner: &Type) -> String {
+  let inner_ts_type = to_ts_type(inner);
+  match inner_ts_type.contains(' ') {
+    true => format!("({})[]", inner_ts_type),
+    false => format!("{}[]", inner_ts_type),
+  }
+}
+
+fn get_generic_type_args(arguments: &PathArguments) -> Vec<&Type> {
+  match arguments {
+    PathArguments::AngleBracketed(angle_bracketed_generic_arguments) => {
+      angle_bracketed_generic_arguments
+        .args
+        .iter()
+        .filter_map(|generic_arg| match generic_arg {
+          GenericArgument::Type(ty) => Some(ty),
+          _ => None,
+        })
+        .collect()
+    }
+    _ => vec![],
+  }
+}
+
+fn write_ts_type_definition_file(
+  type_name_ident: &Ident,
+  ts_type_definition: &str,
+) -> io::Result<()> {
+  let out_dir = env::var(TS_TYPE_OUT_DIR_ENV_VAR).or_else(|_| env::var("OUT_DIR"));
+  if let Ok(out_dir) = out_dir {
+    let out_dir = PathBuf::from(out_dir);
+    fs::create_dir_all(&out_dir)?;
+    fs::write(
+      out_dir.join(format!("{}.d.ts", type_name_ident)),
+      ts_type_definition,
+    )?;
+  }
+  Ok(())
+}
@@ -0,0 +1,136 @@
+/*
+ *   Copyright (c) 2022 Nazmul Idris
+ *   All rights reserved.
+
+ *   Licensed under the Apache License, Version 2.0 (the "License");
+ *   you may not use this file except in compliance with the License.
+ *   You may obtain a copy of the License at
+
+ *   http://www.apache.org/licenses/LICENSE-2.0
+
+ *   Unless required by applicable law or agreed to in writing, software
+ *   distributed under the License is distributed on an "AS IS" BASIS,
+ *   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *   See the License for the specific language governing permissions and
+ *   limitations under the License.
+*/
+
+//! # Watch macro expansion
+//!
+//! To watch for changes run this script:
+//! `./cargo-watch-macro-expand-one-test.fish test_derive_macro_ts_type`
+//!
+//! # Watch test output
+//!
+//! To watch for test output run this script:
+//! `./cargo-watch-one-test.fish test_derive_macro_ts_type`
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use my_proc_macros_lib::TsType;
+
+#[test]
+fn test_proc_macro_struct() {
+  #[derive(TsType)]
+  struct Contact {
+    id: usize,
+    name: String,
+    email: Option<String>,
+    tags: Vec<String>,
+    scores: HashMap<String, f64>,
+    is_favorite: bool,
+    r#type: ContactType,
+  }
+
+  #[derive(TsType)]
+  enum ContactType {
+    Personal,
+    Work,
+  }
+
+  assert_eq!(
+    Contact::ts_type_definition(),
+    "export interface Contact {
+  id: number;
+  name: string;
+  email: string | null;
+  tags: string[];
+  scores: Record<string, number>;
+  is_favorite: boolean;
+  type: ContactType;
+}
+"
+  );
+  assert_eq!(
+    ContactType::ts_type_definition(),
+    "export type ContactType =
+  | \"Personal\"
+  | \"Work\";
+"
+  );
+}
+
+#[test]
+fn test_proc_macro_enum() {
+  #[derive(TsType)]
+  enum Action {
+    Reset,
+    AddContact(String, Option<u32>),
+    RemoveContactById(usize),
+    Search { query: String, limit: u8 },
+  }
+
+  assert_eq!(
+    Action::ts_type_definition(),
+    "export type Action =
+  | \"Reset\"
+  | { AddContact: [string, number | null] }
+  | { RemoveContactById: number }
+  | { Search: { query: string; limit: number; } };
+"
+  );
+}
+
+#[test]
+fn test_proc_macro_tuple_and_unit_structs() {
+  #[derive(TsType)]
+  struct Id(u64);
+
+  #[derive(TsType)]
+  struct Pair(i32, Box<Vec<Option<bool>>>);
+
+  #[derive(TsType)]
+  struct Marker;
+
+  assert_eq!(Id::ts_type_definition(), "export type Id = number;\n");
+  assert_eq!(
+    Pair::ts_type_definition(),
+    "export type Pair = [number, (boolean | null)[]];\n"
+  );
+  assert_eq!(Marker::ts_type_definition(), "export type Marker = null;\n");
+}
+
+#[test]
+fn test_proc_macro_generics() {
+  #[derive(TsType)]
+  struct Page<T>
+  where
+    T: std::fmt::Debug,
+  {
+    items: Vec<T>,
+    next: Option<Box<Page<T>>>,
+    bounds: (usize, usize),
+  }
+
+  assert_eq!(
+    Page::<i32>::ts_type_definition(),
+    "export interface Page<T> {
+  items: T[];
+  next: Page<T> | null;
+  bounds: [number, number];
+}
+"
+  );
+}
@@ -0,0 +1,309 @@
+/*
+ Copyright 2022 Nazmul Idris
+
+ Licensed under the Apache License, Version 2.0 (the "License");
+ you may not use this file except in compliance with the License.
+ You may obtain a copy of the License at
+
+      https://www.apache.org/licenses/LICENSE-2.0
+
+ Unless required by applicable law or agreed to in writing, software
+ distributed under the License is distributed on an "AS IS" BASIS,
+ WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ See the License for the specific language governing permissions and
+ limitations under the License.
+*/
+
+//! # Email addresses
+//! ----------------------------------------------------------------------------
+//! [parse] checks the syntax of an email address. It is "RFC-light": it accepts the
+//! addresses that people actually type (eg: `jane.doe+work@example.co.uk`), and rejects
+//! the rest w/ an [EmailError] that can be shown to the user. Quoted local parts (eg:
+//! `"jane doe"@example.com`) & IP address domains are valid per the RFC, but are
+//! rejected here.
+//!
+//! [suggest_domain] catches typos in common email domains, eg: `jane@gamil.com` ->
+//! `jane@gmail.com`. It uses the edit distance (where swapping 2 adjacent characters is
+//! a single edit) between the domain and each of [COMMON_DOMAINS].
+//!
+//! - Email address syntax: <https://en.wikipedia.org/wiki/Email_address#Syntax>
+//! - Edit distance: <https://en.wikipedia.org/wiki/Damerau%E2%80%93Levenshtein_distance>
+
+use std::fmt;
+
+pub fn run() {}
+
+pub const COMMON_DOMAINS: [&str; 12] = [
+  "gmail.com",
+  "googlemail.com",
+  "yahoo.com",
+  "hotmail.com",
+  "outlook.com",
+  "live.com",
+  "msn.com",
+  "icloud.com",
+  "me.com",
+  "aol.com",
+  "protonmail.com",
+  "proton.me",
+];
+
+const MAX_LOCAL_PART_LEN: usize = 64;
+const MAX_DOMAIN_LEN: usize = 253;
+const MAX_LABEL_LEN: usize = 63;
+
+/// Characters (besides ASCII letters & digits) that are allowed in a local part w/out
+/// quotes.
+const LOCAL_PART_SPECIAL_CHARS: &str = "!#$%&'*+-/=?^_`{|}~.";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmailError {
+  Empty,
+  MissingAt,
+  MultipleAt,
+  InvalidLocalPart,
+  InvalidDomain,
+}
+
+impl fmt::Display for EmailError {
+  fn fmt(
+    &self,
+    f: &mut fmt::Formatter<'_>,
+  ) -> fmt::Result {
+    let message = match self {
+      EmailError::Empty => "Email address is empty",
+      EmailError::MissingAt => "Email address is missing an '@'",
+      EmailError::MultipleAt => "Email address can only have one '@'",
+      EmailError::InvalidLocalPart => "The part before the '@' is not valid",
+      EmailError::InvalidDomain => "The part after the '@' is not a valid domain",
+    };
+    write!(f, "{}", message)
+  }
+}
+
+impl std::error::Error for EmailError {}
+
+/// The domain is lowercased, since domains are case insensitive (the local part is
+/// kept as is).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EmailAddress {
+  local_part: String,
+  domain: String,
+}
+
+impl EmailAddress {
+  pub fn get_local_part(&self) -> &str {
+    &self.local_part
+  }
+
+  pub fn get_domain(&self) -> &str {
+    &self.domain
+  }
+}
+
+impl fmt::Display for EmailAddress {
+  fn fmt(
+    &self,
+    f: &mut fmt::Formatter<'_>,
+  ) -> fmt::Result {
+    write!(f, "{}@{}", self.local_part, self.domain)
+  }
+}
+
+pub fn parse(input: &str) -> Result<EmailAddress, EmailError> {
+  let input = input.trim();
+  if input.is_empty() {
+    return Err(EmailError::Empty);
+  }
+  let (local_part, domain) = match input.split_once('@') {
+    Some((_, domain)) if domain.contains('@') => return Err(EmailError::MultipleAt),
+    Some(parts) => parts,
+    None => return Err(EmailError::MissingAt),
+  };
+  if !is_valid_local_part(local_part) {
+    return Err(EmailError::InvalidLocalPart);
+  }
+  if !is_valid_domain(domain) {
+    return Err(EmailError::InvalidDomain);
+  }
+  Ok(EmailAddress {
+    local_part: local_part.to_string(),
+    domain: domain.to_lowercase(),
+  })
+}
+
+/// Dots are allowed, but not at the start or end, or twice in a row.
+fn is_valid_local_part(local_part: &str) -> bool {
+  !local_part.is_empty()
+    && local_part.len() <= MAX_LOCAL_PART_LEN
+    && local_part
+      .chars()
+      .all(|ch| ch.is_ascii_alphanumeric() || LOCAL_PART_SPECIAL_CHARS.contains(ch))
+    && local_part.split('.').all(|atom| !atom.is_empty())
+}
+
+/// At least 2 labels (eg: `example.com`), each made of letters, digits, and `-` (but not
+/// at the start or end). The last label (top level domain) has to be all letters.
+fn is_valid_domain(domain: &str) -> bool {
+  let labels = domain.split('.').collect::<Vec<&str>>();
+  let is_valid_label = |label: &&str| {
+    (1..=MAX_LABEL_LEN).contains(&label.len())
+      && label
+        .chars()
+        .all(|ch| ch.is_ascii_alphanumeric() || ch == '-')
+      && !label.starts_with('-')
+      && !label.ends_with('-')
+  };
+  domain.len() <= MAX_DOMAIN_LEN
+    && labels.len() >= 2
+    && labels.iter().all(is_valid_label)
+    && labels
+      .last()
+      .is_some_and(|tld| tld.len() >= 2 && tld.chars().all(|ch| ch.is_ascii_alphabetic()))
+}
+
+/// Returns the address w/ its domain replaced by the closest of [COMMON_DOMAINS], if the
+/// domain looks like a typo of it (eg: `jane@gamil.com` -> `jane@gmail.com`). Returns
+/// `None` if the address isn't valid, if its domain is already a common domain, or if
+/// no common domain is close enough. Short domains have to be closer, so that eg:
+/// `xy.com` isn't "corrected" to `me.com`.
+pub fn suggest_domain(input: &str) -> Option<String> {
+  let address = parse(input).ok()?;
+  let domain = address.get_domain();
+  if COMMON_DOMAINS.contains(&domain) {
+    return None;
+  }
+  let max_distance = match domain.len() {
+    0..=6 => 1,
+    _ => 2,
+  };
+  let (closest_domain, distance) = COMMON_DOMAINS
+    .iter()
+    .map(|common_domain| (*common_domain, get_edit_distance(domain, common_domain)))
+    .min_by_key(|(_, distance)| *distance)?;
+  match distance <= max_distance {
+    true => Some(format!("{}@{}", address.get_local_part(), closest_domain)),
+    false => None,
+  }
+}
+
+/// Optimal string alignment distance: the number of insertions, deletions,
+/// substitutions, & swaps of 2 adjacent characters needed to turn `a` into `b`.
+fn get_edit_distance(
+  a: &str,
+  b: &str,
+) -> usize {
+  let (a, b) = (
+    a.chars().collect::<Vec<char>>(),
+    b.chars().collect::<Vec<char>>(),
+  );
+  // `distances[i][j]` is the distance between the first `i` chars of `a` & the first
+  // `j` chars of `b`.
+  let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+  for (i, row) in distances.iter_mut().enumerate() {
+    row[0] = i;
+  }
+  for (j, distance) in distances[0].iter_mut().enumerate() {
+    *distance = j;
+  }
+  for i in 1..=a.len() {
+    for j in 1..=b.len() {
+      let cost = match a[i - 1] == b[j - 1] {
+        true => 0,
+        false => 1,
+      };
+      let mut distance = (distances[i - 1][j] + 1)
+        .min(distances[i][j - 1] + 1)
+        .min(distances[i - 1][j - 1] + cost);
+      if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+        distance = distance.min(distances[i - 2][j - 2] + 1);
+      }
+      distances[i][j] = distance;
+    }
+  }
+  distances[a.len()][b.len()]
+}
+
+#[test]
+fn test_email_parse_valid_addresses() {
+  for input in [
+    "jane@example.com",
+    "jane.doe+work@example.co.uk",
+    "o'brien@my-company.ie",
+    "x@a1.io",
+    "  padded@example.com  ",
+  ] {
+    assert!(parse(input).is_ok(), "{}", input);
+  }
+  let address = parse("Jane.Doe@Example.COM").unwrap();
+  assert_eq!(address.get_local_part(), "Jane.Doe");
+  assert_eq!(address.get_domain(), "example.com");
+  assert_eq!(address.to_string(), "Jane.Doe@example.com");
+}
+
+#[test]
+fn test_email_parse_invalid_addresses() {
+  let matrix = [
+    ("", EmailError::Empty),
+    ("jane.example.com", EmailError::MissingAt),
+    ("jane@doe@example.com", EmailError::MultipleAt),
+    ("@example.com", EmailError::InvalidLocalPart),
+    (".jane@example.com", EmailError::InvalidLocalPart),
+    ("jane.@example.com", EmailError::InvalidLocalPart),
+    ("ja..ne@example.com", EmailError::InvalidLocalPart),
+    ("jane doe@example.com", EmailError::InvalidLocalPart),
+    ("jane@", EmailError::InvalidDomain),
+    ("jane@localhost", EmailError::InvalidDomain),
+    ("jane@example..com", EmailError::InvalidDomain),
+    ("jane@-example.com", EmailError::InvalidDomain),
+    ("jane@example.c", EmailError::InvalidDomain),
+    ("jane@example.c0m", EmailError::InvalidDomain),
+    ("jane@exa_mple.com", EmailError::InvalidDomain),
+  ];
+  for (input, error) in matrix {
+    assert_eq!(parse(input), Err(error), "{}", input);
+  }
+  let long_local_part = format!("{}@example.com", "x".repeat(MAX_LOCAL_PART_LEN + 1));
+  assert_eq!(parse(&long_local_part), Err(EmailError::InvalidLocalPart));
+  assert_eq!(
+    EmailError::MissingAt.to_string(),
+    "Email address is missing an '@'"
+  );
+}
+
+#[test]
+fn test_email_suggest_domain() {
+  let matrix = [
+    ("jane@gamil.com", Some("jane@gmail.com")),
+    ("jane@gmial.com", Some("jane@gmail.com")),
+    ("jane@gmail.co", Some("jane@gmail.com")),
+    ("jane@GMAL.COM", Some("jane@gmail.com")),
+    ("jane@yaho.com", Some("jane@yahoo.com")),
+    ("jane@hotmial.com", Some("jane@hotmail.com")),
+    ("jane@outlok.com", Some("jane@outlook.com")),
+    ("jane@iclod.com", Some("jane@icloud.com")),
+    // Already a common domain.
+    ("jane@gmail.com", None),
+    ("jane@me.com", None),
+    // Not close to any common domain.
+    ("jane@example.com", None),
+    ("jane@company.io", None),
+    // Short domains have to be closer.
+    ("jane@xy.com", None),
+    // Not a valid address.
+    ("jane@gamil", None),
+  ];
+  for (input, suggestion) in matrix {
+    assert_eq!(suggest_domain(input).as_deref(), suggestion, "{}", input);
+  }
+}
+
+#[test]
+fn test_email_edit_distance() {
+  assert_eq!(get_edit_distance("gmail.com", "gmail.com"), 0);
+  assert_eq!(get_edit_distance("gamil.com", "gmail.com"), 1);
+  assert_eq!(get_edit_distance("gmal.com", "gmail.com"), 1);
+  assert_eq!(get_edit_distance("gmaill.com", "gmail.com"), 1);
+  assert_eq!(get_edit_distance("kitten", "sitting"), 3);
+  assert_eq!(get_edit_distance("", "abc"), 3);
+}
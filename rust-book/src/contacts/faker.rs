@@ -0,0 +1,237 @@
+/*
+ Copyright 2022 Nazmul Idris
+
+ Licensed under the Apache License, Version 2.0 (the "License");
+ you may not use this file except in compliance with the License.
+ You may obtain a copy of the License at
+
+      https://www.apache.org/licenses/LICENSE-2.0
+
+ Unless required by applicable law or agreed to in writing, software
+ distributed under the License is distributed on an "AS IS" BASIS,
+ WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ See the License for the specific language governing permissions and
+ limitations under the License.
+*/
+
+//! # Faker
+//! ----------------------------------------------------------------------------
+//! [Faker] generates random (but realistic looking) contacts from the word lists in this
+//! file, so that no network access is needed. A [Faker] that is created w/
+//! [Faker::with_seed] always generates the same contacts, which is handy for tests &
+//! demos.
+//!
+//! The generated data is safe to use:
+//! 1. Emails use the domains that are reserved for examples (eg: `example.com`).
+//! 2. Phone numbers use the `555-0100` to `555-0199` range, which is reserved for
+//!    fictional use in North America.
+//!
+//! - Reserved domains: <https://www.rfc-editor.org/rfc/rfc2606>
+//! - Fictional phone numbers: <https://en.wikipedia.org/wiki/555_(telephone_number)>
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+pub fn run() {}
+
+const FIRST_NAMES: [&str; 24] = [
+  "Aisha", "Ben", "Carlos", "Dana", "Elena", "Farid", "Grace", "Hiro", "Ines", "Jamal",
+  "Kira", "Liam", "Maya", "Nikhil", "Olga", "Priya", "Quinn", "Rosa", "Sven", "Tariq",
+  "Uma", "Victor", "Wei", "Zoe",
+];
+
+const LAST_NAMES: [&str; 24] = [
+  "Adams", "Baker", "Chen", "Diaz", "Evans", "Fischer", "Garcia", "Haddad", "Ito",
+  "Johnson", "Kowalski", "Lopez", "Mensah", "Nguyen", "O'Brien", "Patel", "Rossi",
+  "Schmidt", "Tanaka", "Usman", "Varga", "Walker", "Yilmaz", "Zhang",
+];
+
+const EMAIL_DOMAINS: [&str; 3] = ["example.com", "example.org", "example.net"];
+
+const AREA_CODES: [&str; 10] = [
+  "206", "212", "303", "312", "404", "415", "512", "617", "702", "808",
+];
+
+const STREET_NAMES: [&str; 12] = [
+  "Oak", "Maple", "Cedar", "Pine", "Elm", "Lake", "Hill", "Park", "Sunset", "River",
+  "Mill", "Church",
+];
+
+const STREET_SUFFIXES: [&str; 6] = ["St", "Ave", "Rd", "Blvd", "Ln", "Way"];
+
+/// (City, state).
+const CITIES: [(&str, &str); 10] = [
+  ("Seattle", "WA"),
+  ("New York", "NY"),
+  ("Denver", "CO"),
+  ("Chicago", "IL"),
+  ("Atlanta", "GA"),
+  ("San Francisco", "CA"),
+  ("Austin", "TX"),
+  ("Boston", "MA"),
+  ("Las Vegas", "NV"),
+  ("Honolulu", "HI"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FakeContact {
+  pub name: String,
+  pub email: String,
+  pub phone: String,
+  pub address: String,
+}
+
+pub struct Faker {
+  rng: StdRng,
+}
+
+impl Faker {
+  /// Generates different contacts each time.
+  pub fn new() -> Self {
+    Faker {
+      rng: StdRng::from_entropy(),
+    }
+  }
+
+  /// Generates the same contacts for the same `seed`.
+  pub fn with_seed(seed: u64) -> Self {
+    Faker {
+      rng: StdRng::seed_from_u64(seed),
+    }
+  }
+
+  pub fn first_name(&mut self) -> &'static str {
+    self.pick(&FIRST_NAMES)
+  }
+
+  pub fn last_name(&mut self) -> &'static str {
+    self.pick(&LAST_NAMES)
+  }
+
+  pub fn name(&mut self) -> String {
+    format!("{} {}", self.first_name(), self.last_name())
+  }
+
+  /// Eg: `"grace.ito42@example.org"`.
+  pub fn email_for(
+    &mut self,
+    first_name: &str,
+    last_name: &str,
+  ) -> String {
+    format!(
+      "{}.{}{}@{}",
+      first_name.to_lowercase(),
+      last_name.to_lowercase(),
+      self.rng.gen_range(1..100),
+      self.pick(&EMAIL_DOMAINS)
+    )
+  }
+
+  pub fn email(&mut self) -> String {
+    let (first_name, last_name) = (self.first_name(), self.last_name());
+    self.email_for(first_name, last_name)
+  }
+
+  /// Eg: `"(415) 555-0142"`.
+  pub fn phone(&mut self) -> String {
+    format!(
+      "({}) 555-01{:02}",
+      self.pick(&AREA_CODES),
+      self.rng.gen_range(0..100)
+    )
+  }
+
+  /// Eg: `"1234 Maple Ave, Austin, TX 73301"`.
+  pub fn address(&mut self) -> String {
+    let (city, state) = self.pick(&CITIES);
+    format!(
+      "{} {} {}, {}, {} {:05}",
+      self.rng.gen_range(1..10_000),
+      self.pick(&STREET_NAMES),
+      self.pick(&STREET_SUFFIXES),
+      city,
+      state,
+      self.rng.gen_range(10_000..100_000)
+    )
+  }
+
+  /// The email address is made from the name.
+  pub fn contact(&mut self) -> FakeContact {
+    let (first_name, last_name) = (self.first_name(), self.last_name());
+    FakeContact {
+      name: format!("{} {}", first_name, last_name),
+      email: self.email_for(first_name, last_name),
+      phone: self.phone(),
+      address: self.address(),
+    }
+  }
+
+  pub fn contacts(
+    &mut self,
+    count: usize,
+  ) -> Vec<FakeContact> {
+    (0..count).map(|_| self.contact()).collect()
+  }
+
+  fn pick<T: Copy>(
+    &mut self,
+    items: &[T],
+  ) -> T {
+    *items.choose(&mut self.rng).unwrap()
+  }
+}
+
+impl Default for Faker {
+  fn default() -> Self {
+    Faker::new()
+  }
+}
+
+#[test]
+fn test_faker_same_seed_generates_same_contacts() {
+  let contacts = Faker::with_seed(42).contacts(20);
+  assert_eq!(contacts, Faker::with_seed(42).contacts(20));
+  assert_ne!(contacts, Faker::with_seed(43).contacts(20));
+
+  // Each call continues the same sequence.
+  let mut faker = Faker::with_seed(7);
+  let first_half = faker.contacts(5);
+  let second_half = faker.contacts(5);
+  assert_eq!(
+    [first_half, second_half].concat(),
+    Faker::with_seed(7).contacts(10)
+  );
+}
+
+#[test]
+fn test_faker_contacts_are_valid() {
+  use super::{email, phone};
+
+  let mut faker = Faker::with_seed(1);
+  for contact in faker.contacts(200) {
+    let (first_name, last_name) = contact.name.split_once(' ').unwrap();
+    assert!(FIRST_NAMES.contains(&first_name));
+    assert!(LAST_NAMES.contains(&last_name));
+
+    let email = email::parse(&contact.email).unwrap();
+    assert!(email
+      .get_local_part()
+      .starts_with(&format!("{}.", first_name.to_lowercase())));
+    assert!(EMAIL_DOMAINS.contains(&email.get_domain()));
+
+    let phone = phone::parse(&contact.phone, "US").unwrap();
+    assert!(phone.to_e164()[5..].starts_with("55501"));
+
+    let (street, rest) = contact.address.split_once(", ").unwrap();
+    assert!(street.split(' ').next().unwrap().parse::<u32>().is_ok());
+    assert!(CITIES.iter().any(|(city, _)| rest.starts_with(city)));
+  }
+}
+
+#[test]
+fn test_faker_covers_word_lists() {
+  let mut faker = Faker::with_seed(3);
+  let first_names = (0..1_000)
+    .map(|_| faker.first_name())
+    .collect::<std::collections::HashSet<&str>>();
+  assert_eq!(first_names.len(), FIRST_NAMES.len());
+}
@@ -0,0 +1,23 @@
+/*
+ * Copyright (c) 2022 Nazmul Idris. All rights reserved.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Building blocks for the fields of a contact (eg: its phone number & email address),
+//! and a faker that generates contacts. The contacts app itself (its store, middlewares,
+//! and forms) is not in this repo.
+
+pub mod email;
+pub mod faker;
+pub mod phone;
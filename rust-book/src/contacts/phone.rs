@@ -0,0 +1,406 @@
+/*
+ Copyright 2022 Nazmul Idris
+
+ Licensed under the Apache License, Version 2.0 (the "License");
+ you may not use this file except in compliance with the License.
+ You may obtain a copy of the License at
+
+      https://www.apache.org/licenses/LICENSE-2.0
+
+ Unless required by applicable law or agreed to in writing, software
+ distributed under the License is distributed on an "AS IS" BASIS,
+ WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ See the License for the specific language governing permissions and
+ limitations under the License.
+*/
+
+//! # Phone numbers
+//! ----------------------------------------------------------------------------
+//! [parse] turns a phone number the way a person typed it (eg: `"(415) 555-2671"`, or
+//! `"+44 (0)20 7946 0958"`) into a [PhoneNumber], which can be:
+//! 1. Compared w/ other numbers (eg: to find duplicate contacts), since it is stored in
+//!    its canonical E.164 form (`+` country calling code & national number, eg:
+//!    `+14155552671`).
+//! 2. Formatted for display, eg: `+44 20 7946 0958` or `020 7946 0958`.
+//!
+//! The country is inferred from the number if it starts w/ `+` or `00` (the
+//! international prefix), otherwise the number is treated as a national number in the
+//! given default region. Only the regions in [REGIONS] are known, and the way that they
+//! group digits for display is simplified.
+//!
+//! - E.164: <https://en.wikipedia.org/wiki/E.164>
+//! - National conventions: <https://en.wikipedia.org/wiki/National_conventions_for_writing_telephone_numbers>
+
+use std::{fmt, ops::RangeInclusive};
+
+pub fn run() {}
+
+#[derive(Debug)]
+pub struct Region {
+  /// ISO 3166-1 alpha-2 code, eg: `"US"`.
+  pub code: &'static str,
+  pub calling_code: &'static str,
+  /// Dialed before a national number w/in the region (eg: the `0` in `020 7946 0958`).
+  /// Some regions (eg: Spain) don't have one.
+  pub trunk_prefix: &'static str,
+  /// Shown before a national number when it is formatted (the `1` in the US is dialed,
+  /// but not shown).
+  pub national_display_prefix: &'static str,
+  /// Number of digits in a national number (w/out the trunk prefix).
+  pub national_lengths: RangeInclusive<usize>,
+  /// How the digits of a national number are grouped for display. The first entry
+  /// whose prefix matches the national number is used, and the last group takes the
+  /// rest of the digits.
+  pub groups_by_prefix: &'static [(&'static str, &'static [usize])],
+}
+
+/// The calling codes don't share prefixes (eg: there's no `4` since there's `44`), so
+/// the first one that matches the start of an international number is the right one.
+/// The US shares `1` w/ the rest of the North American Numbering Plan.
+pub const REGIONS: [Region; 8] = [
+  Region {
+    code: "US",
+    calling_code: "1",
+    trunk_prefix: "1",
+    national_display_prefix: "",
+    national_lengths: 10..=10,
+    groups_by_prefix: &[("", &[3, 3, 4])],
+  },
+  Region {
+    code: "GB",
+    calling_code: "44",
+    trunk_prefix: "0",
+    national_display_prefix: "0",
+    national_lengths: 9..=10,
+    groups_by_prefix: &[("2", &[2, 4, 4]), ("", &[4, 6])],
+  },
+  Region {
+    code: "DE",
+    calling_code: "49",
+    trunk_prefix: "0",
+    national_display_prefix: "0",
+    national_lengths: 6..=11,
+    groups_by_prefix: &[
+      ("1", &[3]),
+      ("30", &[2]),
+      ("40", &[2]),
+      ("69", &[2]),
+      ("89", &[2]),
+      ("", &[4]),
+    ],
+  },
+  Region {
+    code: "FR",
+    calling_code: "33",
+    trunk_prefix: "0",
+    national_display_prefix: "0",
+    national_lengths: 9..=9,
+    groups_by_prefix: &[("", &[1, 2, 2, 2, 2])],
+  },
+  Region {
+    code: "IN",
+    calling_code: "91",
+    trunk_prefix: "0",
+    national_display_prefix: "0",
+    national_lengths: 10..=10,
+    groups_by_prefix: &[("", &[5, 5])],
+  },
+  Region {
+    code: "JP",
+    calling_code: "81",
+    trunk_prefix: "0",
+    national_display_prefix: "0",
+    national_lengths: 9..=10,
+    groups_by_prefix: &[("3", &[1, 4, 4]), ("6", &[1, 4, 4]), ("", &[2, 4, 4])],
+  },
+  Region {
+    code: "AU",
+    calling_code: "61",
+    trunk_prefix: "0",
+    national_display_prefix: "0",
+    national_lengths: 9..=9,
+    groups_by_prefix: &[("4", &[3, 3, 3]), ("", &[1, 4, 4])],
+  },
+  Region {
+    code: "ES",
+    calling_code: "34",
+    trunk_prefix: "",
+    national_display_prefix: "",
+    national_lengths: 9..=9,
+    groups_by_prefix: &[("", &[3, 2, 2, 2])],
+  },
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PhoneError {
+  Empty,
+  InvalidCharacter(char),
+  UnknownCallingCode,
+  UnknownRegion(String),
+  InvalidLength { region: &'static str, digits: usize },
+}
+
+impl fmt::Display for PhoneError {
+  fn fmt(
+    &self,
+    f: &mut fmt::Formatter<'_>,
+  ) -> fmt::Result {
+    match self {
+      PhoneError::Empty => write!(f, "phone number is empty"),
+      PhoneError::InvalidCharacter(ch) => {
+        write!(f, "'{}' is not allowed in a phone number", ch)
+      }
+      PhoneError::UnknownCallingCode => write!(f, "unknown country calling code"),
+      PhoneError::UnknownRegion(region) => write!(f, "unknown region '{}'", region),
+      PhoneError::InvalidLength { region, digits } => write!(
+        f,
+        "{} digits is not a valid length for a phone number in {}",
+        digits, region
+      ),
+    }
+  }
+}
+
+impl std::error::Error for PhoneError {}
+
+/// Two numbers that are typed differently (eg: `"(415) 555-2671"` & `"+1 415 555 2671"`)
+/// are equal if they are the same E.164 number.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PhoneNumber {
+  region: &'static str,
+  calling_code: &'static str,
+  /// Digits only, w/out the trunk prefix.
+  national_number: String,
+}
+
+impl PhoneNumber {
+  pub fn get_region(&self) -> &'static str {
+    self.region
+  }
+
+  /// Eg: `+442079460958`.
+  pub fn to_e164(&self) -> String {
+    format!("+{}{}", self.calling_code, self.national_number)
+  }
+
+  /// Eg: `+44 20 7946 0958`.
+  pub fn format_international(&self) -> String {
+    format!("+{} {}", self.calling_code, self.get_groups().join(" "))
+  }
+
+  /// Eg: `020 7946 0958`.
+  pub fn format_national(&self) -> String {
+    format!(
+      "{}{}",
+      find_region(self.region).unwrap().national_display_prefix,
+      self.get_groups().join(" ")
+    )
+  }
+
+  fn get_groups(&self) -> Vec<&str> {
+    let region = find_region(self.region).unwrap();
+    let (_, sizes) = region
+      .groups_by_prefix
+      .iter()
+      .find(|(prefix, _)| self.national_number.starts_with(prefix))
+      .unwrap();
+    let mut groups = vec![];
+    let mut rest = self.national_number.as_str();
+    for size in sizes.iter() {
+      if rest.len() <= *size {
+        break;
+      }
+      let (group, remainder) = rest.split_at(*size);
+      groups.push(group);
+      rest = remainder;
+    }
+    if !rest.is_empty() {
+      groups.push(rest);
+    }
+    groups
+  }
+}
+
+impl fmt::Display for PhoneNumber {
+  fn fmt(
+    &self,
+    f: &mut fmt::Formatter<'_>,
+  ) -> fmt::Result {
+    write!(f, "{}", self.format_international())
+  }
+}
+
+/// `default_region` (eg: `"US"`) is used if `input` doesn't start w/ `+` or `00`. Spaces,
+/// `-`, `.`, `/`, and parentheses are ignored.
+pub fn parse(
+  input: &str,
+  default_region: &str,
+) -> Result<PhoneNumber, PhoneError> {
+  let input = input.trim();
+  if input.is_empty() {
+    return Err(PhoneError::Empty);
+  }
+  let (has_plus, rest) = match input.strip_prefix('+') {
+    Some(rest) => (true, rest),
+    None => (false, input),
+  };
+  if let Some(ch) = rest
+    .chars()
+    .find(|ch| !ch.is_ascii_digit() && !" -./()".contains(*ch))
+  {
+    return Err(PhoneError::InvalidCharacter(ch));
+  }
+  let digits = rest
+    .chars()
+    .filter(char::is_ascii_digit)
+    .collect::<String>();
+
+  let international_digits = match (has_plus, digits.strip_prefix("00")) {
+    (true, _) => Some(digits.as_str()),
+    (false, Some(rest)) => Some(rest),
+    (false, None) => None,
+  };
+
+  let (region, national_number) = match international_digits {
+    Some(digits) => {
+      let region = REGIONS
+        .iter()
+        .find(|region| digits.starts_with(region.calling_code))
+        .ok_or(PhoneError::UnknownCallingCode)?;
+      let national_number = &digits[region.calling_code.len()..];
+      // Eg: the `(0)` in `+44 (0)20 7946 0958`.
+      let national_number = match region.trunk_prefix {
+        "0" => national_number.strip_prefix('0').unwrap_or(national_number),
+        _ => national_number,
+      };
+      (region, national_number)
+    }
+    None => {
+      let region = find_region(default_region)
+        .ok_or_else(|| PhoneError::UnknownRegion(default_region.to_string()))?;
+      let national_number = match digits.strip_prefix(region.trunk_prefix) {
+        Some(rest) if region.national_lengths.contains(&rest.len()) => rest,
+        _ => digits.as_str(),
+      };
+      (region, national_number)
+    }
+  };
+
+  if !region.national_lengths.contains(&national_number.len()) {
+    return Err(PhoneError::InvalidLength {
+      region: region.code,
+      digits: national_number.len(),
+    });
+  }
+
+  Ok(PhoneNumber {
+    region: region.code,
+    calling_code: region.calling_code,
+    national_number: national_number.to_string(),
+  })
+}
+
+fn find_region(code: &str) -> Option<&'static Region> {
+  REGIONS
+    .iter()
+    .find(|region| region.code.eq_ignore_ascii_case(code))
+}
+
+#[test]
+fn test_phone_parse_and_format_matrix() {
+  // (input, default region, E.164, international format).
+  let matrix = [
+    ("(415) 555-2671", "US", "+14155552671", "+1 415 555 2671"),
+    ("415.555.2671", "US", "+14155552671", "+1 415 555 2671"),
+    ("1-415-555-2671", "US", "+14155552671", "+1 415 555 2671"),
+    ("+1 415 555 2671", "GB", "+14155552671", "+1 415 555 2671"),
+    ("001 415 555 2671", "GB", "+14155552671", "+1 415 555 2671"),
+    ("020 7946 0958", "GB", "+442079460958", "+44 20 7946 0958"),
+    (
+      "+44 (0)20 7946 0958",
+      "US",
+      "+442079460958",
+      "+44 20 7946 0958",
+    ),
+    ("07911 123456", "gb", "+447911123456", "+44 7911 123456"),
+    ("030 1234567", "DE", "+49301234567", "+49 30 1234567"),
+    ("0151/23456789", "DE", "+4915123456789", "+49 151 23456789"),
+    ("06 12 34 56 78", "FR", "+33612345678", "+33 6 12 34 56 78"),
+    ("+91 98765 43210", "US", "+919876543210", "+91 98765 43210"),
+    ("03-1234-5678", "JP", "+81312345678", "+81 3 1234 5678"),
+    ("090-1234-5678", "JP", "+819012345678", "+81 90 1234 5678"),
+    ("0412 345 678", "AU", "+61412345678", "+61 412 345 678"),
+    ("(02) 9876 5432", "AU", "+61298765432", "+61 2 9876 5432"),
+    ("612 34 56 78", "ES", "+34612345678", "+34 612 34 56 78"),
+    ("0034 912 345 678", "US", "+34912345678", "+34 912 34 56 78"),
+  ];
+  for (input, default_region, e164, international) in matrix {
+    let phone_number = parse(input, default_region).unwrap();
+    assert_eq!(phone_number.to_e164(), e164, "{}", input);
+    assert_eq!(
+      phone_number.format_international(),
+      international,
+      "{}",
+      input
+    );
+    assert_eq!(phone_number.to_string(), international);
+    // Parsing the canonical form again gives the same number.
+    assert_eq!(parse(e164, "US").unwrap(), phone_number);
+  }
+}
+
+#[test]
+fn test_phone_format_national() {
+  let format_national = |input: &str| parse(input, "US").unwrap().format_national();
+  assert_eq!(format_national("+1 415 555 2671"), "415 555 2671");
+  assert_eq!(format_national("+44 20 7946 0958"), "020 7946 0958");
+  assert_eq!(format_national("+44 7911 123456"), "07911 123456");
+  assert_eq!(format_national("+81 90 1234 5678"), "090 1234 5678");
+  // Spain doesn't have a trunk prefix.
+  assert_eq!(format_national("+34 612 34 56 78"), "612 34 56 78");
+}
+
+#[test]
+fn test_phone_same_number_typed_differently_is_equal() {
+  assert_eq!(
+    parse("(415) 555-2671", "US").unwrap(),
+    parse("+1 415 555 2671", "FR").unwrap()
+  );
+  assert_eq!(parse("+33 6 12 34 56 78", "US").unwrap().get_region(), "FR");
+  assert_ne!(
+    parse("0412 345 678", "AU").unwrap(),
+    parse("0412 345 679", "AU").unwrap()
+  );
+}
+
+#[test]
+fn test_phone_parse_errors() {
+  assert_eq!(parse("  ", "US"), Err(PhoneError::Empty));
+  assert_eq!(
+    parse("415-CALL-NOW", "US"),
+    Err(PhoneError::InvalidCharacter('C'))
+  );
+  assert_eq!(
+    parse("+1 415 + 555", "US"),
+    Err(PhoneError::InvalidCharacter('+'))
+  );
+  assert_eq!(
+    parse("+999 1234", "US"),
+    Err(PhoneError::UnknownCallingCode)
+  );
+  assert_eq!(
+    parse("0412 345 678", "XX"),
+    Err(PhoneError::UnknownRegion("XX".to_string()))
+  );
+  assert_eq!(
+    parse("555-2671", "US"),
+    Err(PhoneError::InvalidLength {
+      region: "US",
+      digits: 7
+    })
+  );
+  assert_eq!(
+    parse("555-2671", "US").unwrap_err().to_string(),
+    "7 digits is not a valid length for a phone number in US"
+  );
+}
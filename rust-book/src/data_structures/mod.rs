@@ -15,6 +15,9 @@
  */
 
 pub mod hashmap;
+pub mod priority_queue;
+pub mod rope;
 pub mod strings;
 pub mod tree;
+pub mod trie;
 pub mod vector;
@@ -0,0 +1,459 @@
+/*
+ Copyright 2022 Nazmul Idris
+
+ Licensed under the Apache License, Version 2.0 (the "License");
+ you may not use this file except in compliance with the License.
+ You may obtain a copy of the License at
+
+      https://www.apache.org/licenses/LICENSE-2.0
+
+ Unless required by applicable law or agreed to in writing, software
+ distributed under the License is distributed on an "AS IS" BASIS,
+ WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ See the License for the specific language governing permissions and
+ limitations under the License.
+*/
+
+//! # Priority queue
+//! ----------------------------------------------------------------------------
+//! [PriorityQueue] is a binary min-heap (the item w/ the lowest priority value is popped first,
+//! like Dijkstra's algorithm, or `nice` values for processes). Unlike
+//! [std::collections::BinaryHeap], it keeps track of where each item is in the heap, so that the
+//! priority of an item that is already queued can be lowered ("decrease-key") in `O(log n)`.
+//!
+//! - Binary heap: <https://en.wikipedia.org/wiki/Binary_heap>
+//! - Decrease key: <https://en.wikipedia.org/wiki/Priority_queue#Operations>
+//!
+//! # Task scheduler
+//! ----------------------------------------------------------------------------
+//! [TaskScheduler] is a cooperative scheduler that runs closures by priority. A task runs until it
+//! returns, and it can ask to be run again by returning [TaskStatus::Yield]. Tasks that are
+//! waiting get "aged" (their priority is lowered w/ decrease-key) each time another task runs, so
+//! that low priority tasks are not starved. A task that yields is queued again at the priority it
+//! was spawned w/, so aging doesn't pile up & flatten all the tasks to the same priority.
+
+use std::{collections::HashMap, hash::Hash};
+
+pub fn run() {}
+
+pub struct PriorityQueue<T, P>
+where
+  T: Eq + Hash + Clone,
+  P: Ord + Clone,
+{
+  heap: Vec<(T, P)>,
+  /// Index of each item in `heap`.
+  indices: HashMap<T, usize>,
+}
+
+impl<T, P> PriorityQueue<T, P>
+where
+  T: Eq + Hash + Clone,
+  P: Ord + Clone,
+{
+  pub fn new() -> Self {
+    PriorityQueue {
+      heap: Vec::new(),
+      indices: HashMap::new(),
+    }
+  }
+
+  pub fn len(&self) -> usize {
+    self.heap.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.heap.is_empty()
+  }
+
+  pub fn contains(
+    &self,
+    item: &T,
+  ) -> bool {
+    self.indices.contains_key(item)
+  }
+
+  pub fn get_priority(
+    &self,
+    item: &T,
+  ) -> Option<&P> {
+    self.indices.get(item).map(|index| &self.heap[*index].1)
+  }
+
+  /// If the item is already queued, its priority is changed instead.
+  pub fn push(
+    &mut self,
+    item: T,
+    priority: P,
+  ) {
+    if self.contains(&item) {
+      self.change_priority(&item, priority);
+      return;
+    }
+    self.heap.push((item.clone(), priority));
+    let index = self.heap.len() - 1;
+    self.indices.insert(item, index);
+    self.sift_up(index);
+  }
+
+  pub fn peek(&self) -> Option<&(T, P)> {
+    self.heap.first()
+  }
+
+  pub fn pop(&mut self) -> Option<(T, P)> {
+    if self.heap.is_empty() {
+      return None;
+    }
+    let last_index = self.heap.len() - 1;
+    self.swap(0, last_index);
+    let (item, priority) = self.heap.pop().unwrap();
+    self.indices.remove(&item);
+    if !self.heap.is_empty() {
+      self.sift_down(0);
+    }
+    Some((item, priority))
+  }
+
+  /// Lowers the priority of a queued item. Returns false (and does nothing) if the item isn't
+  /// queued, or if `priority` isn't lower than its current priority.
+  pub fn decrease_key(
+    &mut self,
+    item: &T,
+    priority: P,
+  ) -> bool {
+    match self.indices.get(item) {
+      Some(index) if priority < self.heap[*index].1 => {
+        let index = *index;
+        self.heap[index].1 = priority;
+        self.sift_up(index);
+        true
+      }
+      _ => false,
+    }
+  }
+
+  /// Raises or lowers the priority of a queued item. Returns false if the item isn't queued.
+  pub fn change_priority(
+    &mut self,
+    item: &T,
+    priority: P,
+  ) -> bool {
+    match self.indices.get(item) {
+      Some(index) => {
+        let index = *index;
+        self.heap[index].1 = priority;
+        self.sift_up(index);
+        self.sift_down(self.indices[item]);
+        true
+      }
+      None => false,
+    }
+  }
+
+  pub fn remove(
+    &mut self,
+    item: &T,
+  ) -> Option<P> {
+    let index = *self.indices.get(item)?;
+    let last_index = self.heap.len() - 1;
+    self.swap(index, last_index);
+    let (removed_item, priority) = self.heap.pop().unwrap();
+    self.indices.remove(&removed_item);
+    // The item that was moved into the removed item's place might have to go up or down.
+    if index < self.heap.len() {
+      let moved_item = self.heap[index].0.clone();
+      self.sift_up(index);
+      self.sift_down(self.indices[&moved_item]);
+    }
+    Some(priority)
+  }
+
+  pub fn get_items(&self) -> Vec<&T> {
+    self.heap.iter().map(|(item, _)| item).collect()
+  }
+
+  fn sift_up(
+    &mut self,
+    mut index: usize,
+  ) {
+    while index > 0 {
+      let parent = (index - 1) / 2;
+      if self.heap[index].1 >= self.heap[parent].1 {
+        break;
+      }
+      self.swap(index, parent);
+      index = parent;
+    }
+  }
+
+  fn sift_down(
+    &mut self,
+    mut index: usize,
+  ) {
+    loop {
+      let (left, right) = (2 * index + 1, 2 * index + 2);
+      let mut smallest = index;
+      if left < self.heap.len() && self.heap[left].1 < self.heap[smallest].1 {
+        smallest = left;
+      }
+      if right < self.heap.len() && self.heap[right].1 < self.heap[smallest].1 {
+        smallest = right;
+      }
+      if smallest == index {
+        break;
+      }
+      self.swap(index, smallest);
+      index = smallest;
+    }
+  }
+
+  /// Swaps two entries in the heap and keeps `indices` in sync.
+  fn swap(
+    &mut self,
+    a: usize,
+    b: usize,
+  ) {
+    self.heap.swap(a, b);
+    self.indices.insert(self.heap[a].0.clone(), a);
+    self.indices.insert(self.heap[b].0.clone(), b);
+  }
+}
+
+impl<T, P> Default for PriorityQueue<T, P>
+where
+  T: Eq + Hash + Clone,
+  P: Ord + Clone,
+{
+  fn default() -> Self {
+    PriorityQueue::new()
+  }
+}
+
+pub type TaskId = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskStatus {
+  /// Run this task again later.
+  Yield,
+  Done,
+}
+
+type Task = Box<dyn FnMut() -> TaskStatus>;
+
+/// Lower priority values run first. Tasks w/ the same priority run in the order they were
+/// scheduled (the sequence number breaks ties).
+pub struct TaskScheduler {
+  queue: PriorityQueue<TaskId, (u32, usize)>,
+  /// Each task along w/ the priority it was spawned w/ (its base priority).
+  tasks: HashMap<TaskId, (u32, Task)>,
+  next_task_id: TaskId,
+  next_sequence: usize,
+}
+
+impl TaskScheduler {
+  pub fn new() -> Self {
+    TaskScheduler {
+      queue: PriorityQueue::new(),
+      tasks: HashMap::new(),
+      next_task_id: 0,
+      next_sequence: 0,
+    }
+  }
+
+  pub fn spawn(
+    &mut self,
+    priority: u32,
+    task: impl FnMut() -> TaskStatus + 'static,
+  ) -> TaskId {
+    let task_id = self.next_task_id;
+    self.next_task_id += 1;
+    self.tasks.insert(task_id, (priority, Box::new(task)));
+    self.enqueue(task_id, priority);
+    task_id
+  }
+
+  /// Moves a waiting task ahead by lowering its priority. This only lasts until the task runs, if
+  /// it yields it is queued again at its base priority. Returns false if the task isn't waiting,
+  /// or if `priority` isn't lower than its current priority.
+  pub fn boost(
+    &mut self,
+    task_id: TaskId,
+    priority: u32,
+  ) -> bool {
+    let sequence = match self.queue.get_priority(&task_id) {
+      Some((_, sequence)) => *sequence,
+      None => return false,
+    };
+    self.queue.decrease_key(&task_id, (priority, sequence))
+  }
+
+  /// Runs the next task once, and returns its id, or `None` if there are no tasks.
+  pub fn run_next(&mut self) -> Option<TaskId> {
+    let (task_id, _) = self.queue.pop()?;
+    let (base_priority, task) = self.tasks.get_mut(&task_id).unwrap();
+    let base_priority = *base_priority;
+    let status = task();
+    self.age_waiting_tasks();
+    match status {
+      TaskStatus::Yield => self.enqueue(task_id, base_priority),
+      TaskStatus::Done => {
+        self.tasks.remove(&task_id);
+      }
+    }
+    Some(task_id)
+  }
+
+  /// Runs tasks until all of them are done, and returns the ids of the tasks in the order that
+  /// they ran.
+  pub fn run_until_idle(&mut self) -> Vec<TaskId> {
+    let mut ran = vec![];
+    while let Some(task_id) = self.run_next() {
+      ran.push(task_id);
+    }
+    ran
+  }
+
+  fn enqueue(
+    &mut self,
+    task_id: TaskId,
+    priority: u32,
+  ) {
+    self.queue.push(task_id, (priority, self.next_sequence));
+    self.next_sequence += 1;
+  }
+
+  fn age_waiting_tasks(&mut self) {
+    let waiting = self
+      .queue
+      .get_items()
+      .into_iter()
+      .copied()
+      .collect::<Vec<TaskId>>();
+    for task_id in waiting {
+      if let Some((priority, sequence)) = self.queue.get_priority(&task_id).copied() {
+        if priority > 0 {
+          self.queue.decrease_key(&task_id, (priority - 1, sequence));
+        }
+      }
+    }
+  }
+}
+
+impl Default for TaskScheduler {
+  fn default() -> Self {
+    TaskScheduler::new()
+  }
+}
+
+#[test]
+fn test_priority_queue_pops_in_priority_order() {
+  let mut queue = PriorityQueue::new();
+  for (item, priority) in [("e", 5), ("a", 1), ("d", 4), ("b", 2), ("c", 3)] {
+    queue.push(item, priority);
+  }
+  assert_eq!(queue.len(), 5);
+  assert_eq!(queue.peek(), Some(&("a", 1)));
+
+  let mut popped = vec![];
+  while let Some((item, _)) = queue.pop() {
+    popped.push(item);
+  }
+  assert_eq!(popped, vec!["a", "b", "c", "d", "e"]);
+  assert!(queue.is_empty());
+}
+
+#[test]
+fn test_priority_queue_decrease_key() {
+  let mut queue = PriorityQueue::new();
+  queue.push("a", 10);
+  queue.push("b", 20);
+  queue.push("c", 30);
+
+  assert!(queue.decrease_key(&"c", 5));
+  assert_eq!(queue.peek(), Some(&("c", 5)));
+  // Not lower than the current priority.
+  assert!(!queue.decrease_key(&"b", 25));
+  // Not queued.
+  assert!(!queue.decrease_key(&"z", 1));
+
+  // Pushing an item that is already queued changes its priority.
+  queue.push("b", 1);
+  assert_eq!(queue.len(), 3);
+  assert_eq!(queue.pop(), Some(("b", 1)));
+
+  assert!(queue.change_priority(&"c", 100));
+  assert_eq!(queue.pop(), Some(("a", 10)));
+  assert_eq!(queue.remove(&"c"), Some(100));
+  assert!(queue.is_empty());
+}
+
+#[test]
+fn test_task_scheduler_runs_by_priority_and_yields() {
+  use std::{cell::RefCell, rc::Rc};
+
+  let log = Rc::new(RefCell::new(Vec::<String>::new()));
+  let mut scheduler = TaskScheduler::new();
+
+  let log_ref = log.clone();
+  let mut slices_left = 2;
+  let worker = scheduler.spawn(1, move || {
+    log_ref.borrow_mut().push(format!("worker {}", slices_left));
+    slices_left -= 1;
+    match slices_left {
+      0 => TaskStatus::Done,
+      _ => TaskStatus::Yield,
+    }
+  });
+
+  let log_ref = log.clone();
+  let background = scheduler.spawn(9, move || {
+    log_ref.borrow_mut().push("background".to_string());
+    TaskStatus::Done
+  });
+
+  let log_ref = log.clone();
+  let urgent = scheduler.spawn(5, move || {
+    log_ref.borrow_mut().push("urgent".to_string());
+    TaskStatus::Done
+  });
+  assert!(scheduler.boost(urgent, 0));
+
+  assert_eq!(
+    scheduler.run_until_idle(),
+    vec![urgent, worker, worker, background]
+  );
+  assert_eq!(
+    *log.borrow(),
+    vec!["urgent", "worker 2", "worker 1", "background"]
+  );
+}
+
+#[test]
+fn test_task_scheduler_ages_waiting_tasks() {
+  let mut scheduler = TaskScheduler::new();
+  // A task that always yields would starve the low priority task, if it weren't for aging.
+  let busy = scheduler.spawn(3, || TaskStatus::Yield);
+  let starved = scheduler.spawn(6, || TaskStatus::Done);
+
+  let mut ran = vec![];
+  while ran.last() != Some(&starved) {
+    ran.push(scheduler.run_next().unwrap());
+  }
+  assert!(ran.iter().filter(|it| **it == busy).count() <= 4);
+}
+
+#[test]
+fn test_task_scheduler_yielded_tasks_keep_their_base_priority() {
+  let mut scheduler = TaskScheduler::new();
+  let high = scheduler.spawn(1, || TaskStatus::Yield);
+  let low = scheduler.spawn(100, || TaskStatus::Yield);
+
+  let ran = (0..400)
+    .map(|_| scheduler.run_next().unwrap())
+    .collect::<Vec<TaskId>>();
+  // The low priority task still gets to run (aging), but the high priority one keeps
+  // dominating, instead of both being aged down to the same priority & taking turns.
+  let last_100 = &ran[300..];
+  assert!(last_100.contains(&low));
+  assert!(last_100.iter().filter(|it| **it == high).count() >= 95);
+}
@@ -0,0 +1,796 @@
+/*
+ Copyright 2022 Nazmul Idris
+
+ Licensed under the Apache License, Version 2.0 (the "License");
+ you may not use this file except in compliance with the License.
+ You may obtain a copy of the License at
+
+      https://www.apache.org/licenses/LICENSE-2.0
+
+ Unless required by applicable law or agreed to in writing, software
+ distributed under the License is distributed on an "AS IS" BASIS,
+ WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ See the License for the specific language governing permissions and
+ limitations under the License.
+*/
+
+//! # Rope
+//! ----------------------------------------------------------------------------
+//! A [Rope] stores text in a binary tree whose leaves are small strings. Each branch
+//! caches the number of bytes & newlines below it, so finding the start of a line is
+//! `O(log n)`, and edits only split & join the nodes along one path, instead of moving
+//! all the bytes after the edit (like `String::insert_str` does).
+//!
+//! 1. Leaves are split on grapheme cluster boundaries, so a user perceived character (eg:
+//!    `"🇺🇸"`) is never split across 2 leaves. This is kept true by edits too, eg: when
+//!    a combining accent is inserted right after an `e` at the end of a leaf, the accent
+//!    is moved into that leaf.
+//! 2. Positions used by the editing API are a row (line index) & a display column. A
+//!    display column that falls in the middle of a wide grapheme cluster (eg: `"😃"` is 2
+//!    columns wide) snaps to the start of that cluster.
+//! 3. The tree is kept height balanced (like an AVL tree): the depths of the 2 children
+//!    of a branch never differ by more than 1. Joining 2 trees only rotates the nodes
+//!    along the edge of the taller tree where the shorter one is attached, so an insert
+//!    or delete costs `O(log n)`, even after lots of edits at the same place.
+//!
+//! - Rope: <https://en.wikipedia.org/wiki/Rope_(data_structure)>
+//! - Join based balanced trees: <https://arxiv.org/abs/1602.02120>
+//! - Grapheme clusters: <https://unicode.org/reports/tr29/>
+
+use std::{fmt, ops::Range};
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+pub fn run() {}
+
+/// Leaves are at most this many bytes (unless a leaf has to grow past this so that a
+/// grapheme cluster isn't split across 2 leaves).
+const MAX_LEAF_BYTES: usize = 256;
+
+#[derive(Debug, Clone)]
+enum Node {
+  Leaf(String),
+  Branch {
+    left: Box<Node>,
+    right: Box<Node>,
+    len_bytes: usize,
+    newlines: usize,
+    depth: usize,
+  },
+}
+
+impl Node {
+  fn len_bytes(&self) -> usize {
+    match self {
+      Node::Leaf(text) => text.len(),
+      Node::Branch { len_bytes, .. } => *len_bytes,
+    }
+  }
+
+  fn newlines(&self) -> usize {
+    match self {
+      Node::Leaf(text) => text.matches('\n').count(),
+      Node::Branch { newlines, .. } => *newlines,
+    }
+  }
+
+  fn depth(&self) -> usize {
+    match self {
+      Node::Leaf(_) => 0,
+      Node::Branch { depth, .. } => *depth,
+    }
+  }
+
+  /// Joins 2 balanced trees, whose text was not next to each other before (eg: after an
+  /// insert or delete), into a balanced tree. If the seam is in the middle of a grapheme
+  /// cluster (eg: `right` starts w/ a combining accent), the rest of the cluster is moved
+  /// from the first leaf of `right` to the last leaf of `left`.
+  fn join(
+    mut left: Node,
+    mut right: Node,
+  ) -> Node {
+    while left.len_bytes() > 0 && right.len_bytes() > 0 {
+      let last_g_c = left
+        .last_leaf()
+        .graphemes(true)
+        .next_back()
+        .unwrap_or_default()
+        .to_string();
+      let joined = format!("{}{}", last_g_c, right.first_leaf());
+      let joined_g_c_len = joined.graphemes(true).next().map_or(0, str::len);
+      if joined_g_c_len <= last_g_c.len() {
+        break;
+      }
+      // The cluster can go past the end of the first leaf, so this loops until the
+      // seam is on a grapheme cluster boundary.
+      let (moved, rest) = right.split(joined_g_c_len - last_g_c.len());
+      let mut moved_text = String::new();
+      moved.collect_into(0..moved.len_bytes(), &mut moved_text);
+      left.push_str_to_last_leaf(&moved_text);
+      right = rest;
+    }
+    Node::join_balanced(left, right)
+  }
+
+  /// Joins 2 balanced trees into a balanced tree. If one tree is more than 1 level
+  /// deeper, the other one is joined w/ its nearest child (recursively), and the result
+  /// is rebalanced on the way back up. The cost is `O(1 + the difference in depth)`.
+  fn join_balanced(
+    left: Node,
+    right: Node,
+  ) -> Node {
+    if left.len_bytes() == 0 {
+      return right;
+    }
+    if right.len_bytes() == 0 {
+      return left;
+    }
+    let (left_depth, right_depth) = (left.depth(), right.depth());
+    if left_depth > right_depth + 1 {
+      let (left_left, left_right) = left.into_children();
+      Node::balance(left_left, Node::join_balanced(left_right, right))
+    } else if right_depth > left_depth + 1 {
+      let (right_left, right_right) = right.into_children();
+      Node::balance(Node::join_balanced(left, right_left), right_right)
+    } else {
+      Node::branch(left, right)
+    }
+  }
+
+  /// Makes a branch from 2 balanced trees whose depths differ by at most 2, w/ a single
+  /// or double rotation if they differ by 2.
+  fn balance(
+    left: Node,
+    right: Node,
+  ) -> Node {
+    let (left_depth, right_depth) = (left.depth(), right.depth());
+    if left_depth > right_depth + 1 {
+      let (left_left, left_right) = left.into_children();
+      if left_left.depth() >= left_right.depth() {
+        Node::branch(left_left, Node::branch(left_right, right))
+      } else {
+        let (left_right_left, left_right_right) = left_right.into_children();
+        Node::branch(
+          Node::branch(left_left, left_right_left),
+          Node::branch(left_right_right, right),
+        )
+      }
+    } else if right_depth > left_depth + 1 {
+      let (right_left, right_right) = right.into_children();
+      if right_right.depth() >= right_left.depth() {
+        Node::branch(Node::branch(left, right_left), right_right)
+      } else {
+        let (right_left_left, right_left_right) = right_left.into_children();
+        Node::branch(
+          Node::branch(left, right_left_left),
+          Node::branch(right_left_right, right_right),
+        )
+      }
+    } else {
+      Node::branch(left, right)
+    }
+  }
+
+  /// Small adjacent leaves are merged, so lots of small edits don't leave lots of tiny
+  /// leaves.
+  fn branch(
+    left: Node,
+    right: Node,
+  ) -> Node {
+    match (left, right) {
+      (Node::Leaf(mut left), Node::Leaf(right))
+        if left.len() + right.len() <= MAX_LEAF_BYTES =>
+      {
+        left.push_str(&right);
+        Node::Leaf(left)
+      }
+      (left, right) => Node::Branch {
+        len_bytes: left.len_bytes() + right.len_bytes(),
+        newlines: left.newlines() + right.newlines(),
+        depth: left.depth().max(right.depth()) + 1,
+        left: Box::new(left),
+        right: Box::new(right),
+      },
+    }
+  }
+
+  fn first_leaf(&self) -> &str {
+    match self {
+      Node::Leaf(text) => text,
+      Node::Branch { left, .. } => left.first_leaf(),
+    }
+  }
+
+  fn last_leaf(&self) -> &str {
+    match self {
+      Node::Leaf(text) => text,
+      Node::Branch { right, .. } => right.last_leaf(),
+    }
+  }
+
+  /// Appends to the last leaf, and updates the cached counts of the branches above it.
+  fn push_str_to_last_leaf(
+    &mut self,
+    text: &str,
+  ) {
+    match self {
+      Node::Leaf(leaf) => leaf.push_str(text),
+      Node::Branch {
+        right,
+        len_bytes,
+        newlines,
+        ..
+      } => {
+        right.push_str_to_last_leaf(text);
+        *len_bytes += text.len();
+        *newlines += text.matches('\n').count();
+      }
+    }
+  }
+
+  fn into_children(self) -> (Node, Node) {
+    match self {
+      Node::Branch { left, right, .. } => (*left, *right),
+      Node::Leaf(_) => unreachable!("a leaf is never deeper than another node"),
+    }
+  }
+
+  /// Splits into `[0, at)` and `[at, len)`. `at` must be on a char boundary.
+  fn split(
+    self,
+    at: usize,
+  ) -> (Node, Node) {
+    match self {
+      Node::Leaf(mut text) => {
+        let right = text.split_off(at);
+        (Node::Leaf(text), Node::Leaf(right))
+      }
+      Node::Branch { left, right, .. } => {
+        let left_len = left.len_bytes();
+        if at < left_len {
+          let (left_left, left_right) = left.split(at);
+          (left_left, Node::join_balanced(left_right, *right))
+        } else {
+          let (right_left, right_right) = right.split(at - left_len);
+          (Node::join_balanced(*left, right_left), right_right)
+        }
+      }
+    }
+  }
+
+  fn collect_into(
+    &self,
+    range: Range<usize>,
+    acc: &mut String,
+  ) {
+    if range.start >= range.end {
+      return;
+    }
+    match self {
+      Node::Leaf(text) => acc.push_str(&text[range]),
+      Node::Branch { left, right, .. } => {
+        let left_len = left.len_bytes();
+        if range.start < left_len {
+          left.collect_into(range.start..range.end.min(left_len), acc);
+        }
+        if range.end > left_len {
+          right.collect_into(
+            range.start.saturating_sub(left_len)..range.end - left_len,
+            acc,
+          );
+        }
+      }
+    }
+  }
+
+  /// Byte offset just after the `n`th newline (`n` starts at 1, and must be <=
+  /// `newlines()`).
+  fn byte_after_nth_newline(
+    &self,
+    n: usize,
+  ) -> usize {
+    match self {
+      Node::Leaf(text) => {
+        let (index, _) = text.match_indices('\n').nth(n - 1).unwrap();
+        index + 1
+      }
+      Node::Branch { left, right, .. } => {
+        let left_newlines = left.newlines();
+        if n <= left_newlines {
+          left.byte_after_nth_newline(n)
+        } else {
+          left.len_bytes() + right.byte_after_nth_newline(n - left_newlines)
+        }
+      }
+    }
+  }
+
+  fn collect_leaves<'a>(
+    &'a self,
+    acc: &mut Vec<&'a str>,
+  ) {
+    match self {
+      Node::Leaf(text) => acc.push(text),
+      Node::Branch { left, right, .. } => {
+        left.collect_leaves(acc);
+        right.collect_leaves(acc);
+      }
+    }
+  }
+
+  /// Builds a balanced tree from the given leaves.
+  fn from_leaves(leaves: &[String]) -> Node {
+    match leaves.len() {
+      0 => Node::Leaf(String::new()),
+      1 => Node::Leaf(leaves[0].clone()),
+      len => Node::join_balanced(
+        Node::from_leaves(&leaves[..len / 2]),
+        Node::from_leaves(&leaves[len / 2..]),
+      ),
+    }
+  }
+}
+
+impl Default for Node {
+  fn default() -> Self {
+    Node::Leaf(String::new())
+  }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Rope {
+  root: Node,
+}
+
+impl From<&str> for Rope {
+  fn from(text: &str) -> Self {
+    Rope {
+      root: Node::from_leaves(&split_into_leaves(text)),
+    }
+  }
+}
+
+impl fmt::Display for Rope {
+  fn fmt(
+    &self,
+    f: &mut fmt::Formatter<'_>,
+  ) -> fmt::Result {
+    self
+      .get_leaves()
+      .into_iter()
+      .try_for_each(|leaf| f.write_str(leaf))
+  }
+}
+
+impl Rope {
+  pub fn new() -> Self {
+    Rope::default()
+  }
+
+  pub fn len_bytes(&self) -> usize {
+    self.root.len_bytes()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len_bytes() == 0
+  }
+
+  /// An empty rope has 1 (empty) line, and a trailing newline starts a new (empty) line.
+  pub fn len_lines(&self) -> usize {
+    self.root.newlines() + 1
+  }
+
+  /// Byte offset of the start of the line at `row`, in `O(log n)`.
+  pub fn line_start_byte(
+    &self,
+    row: usize,
+  ) -> Option<usize> {
+    match row {
+      0 => Some(0),
+      row if row < self.len_lines() => Some(self.root.byte_after_nth_newline(row)),
+      _ => None,
+    }
+  }
+
+  /// The line at `row`, without its trailing newline.
+  pub fn line(
+    &self,
+    row: usize,
+  ) -> Option<String> {
+    let start = self.line_start_byte(row)?;
+    let end = match self.line_start_byte(row + 1) {
+      Some(next_line_start) => next_line_start - 1,
+      None => self.len_bytes(),
+    };
+    Some(self.slice_bytes(start..end))
+  }
+
+  pub fn line_display_width(
+    &self,
+    row: usize,
+  ) -> Option<usize> {
+    self
+      .line(row)
+      .map(|line| UnicodeWidthStr::width(line.as_str()))
+  }
+
+  pub fn lines(&self) -> Lines<'_> {
+    Lines { rope: self, row: 0 }
+  }
+
+  /// `range` must be on char boundaries.
+  pub fn slice_bytes(
+    &self,
+    range: Range<usize>,
+  ) -> String {
+    let mut acc = String::with_capacity(range.len());
+    self.root.collect_into(range, &mut acc);
+    acc
+  }
+
+  /// Display columns past the end of the line are clamped to the end of the line.
+  pub fn slice(
+    &self,
+    row: usize,
+    display_cols: Range<usize>,
+  ) -> Option<String> {
+    let range = self.get_byte_range(row, display_cols)?;
+    Some(self.slice_bytes(range))
+  }
+
+  /// `byte_offset` must be on a char boundary.
+  pub fn insert_at_byte(
+    &mut self,
+    byte_offset: usize,
+    text: &str,
+  ) {
+    if text.is_empty() {
+      return;
+    }
+    let root = std::mem::take(&mut self.root);
+    let (left, right) = root.split(byte_offset);
+    let middle = Node::from_leaves(&split_into_leaves(text));
+    self.root = Node::join(Node::join(left, middle), right);
+  }
+
+  /// Returns false if there's no line at `row`.
+  pub fn insert(
+    &mut self,
+    row: usize,
+    display_col: usize,
+    text: &str,
+  ) -> bool {
+    match self.get_byte_range(row, display_col..display_col) {
+      Some(range) => {
+        self.insert_at_byte(range.start, text);
+        true
+      }
+      None => false,
+    }
+  }
+
+  /// `range` must be on char boundaries.
+  pub fn delete_bytes(
+    &mut self,
+    range: Range<usize>,
+  ) {
+    if range.start >= range.end {
+      return;
+    }
+    let root = std::mem::take(&mut self.root);
+    let (left, rest) = root.split(range.start);
+    let (_, right) = rest.split(range.len());
+    self.root = Node::join(left, right);
+  }
+
+  /// Deletes within a single line (the newline at the end of the line is never deleted).
+  /// Returns false if there's no line at `row`.
+  pub fn delete(
+    &mut self,
+    row: usize,
+    display_cols: Range<usize>,
+  ) -> bool {
+    match self.get_byte_range(row, display_cols) {
+      Some(range) => {
+        self.delete_bytes(range);
+        true
+      }
+      None => false,
+    }
+  }
+
+  fn get_byte_range(
+    &self,
+    row: usize,
+    display_cols: Range<usize>,
+  ) -> Option<Range<usize>> {
+    let line_start = self.line_start_byte(row)?;
+    let line = self.line(row)?;
+    let start = line_start + byte_offset_of_display_col(&line, display_cols.start);
+    let end = line_start + byte_offset_of_display_col(&line, display_cols.end);
+    Some(start..end.max(start))
+  }
+
+  fn get_leaves(&self) -> Vec<&str> {
+    let mut leaves = vec![];
+    self.root.collect_leaves(&mut leaves);
+    leaves
+  }
+}
+
+pub struct Lines<'a> {
+  rope: &'a Rope,
+  row: usize,
+}
+
+impl Iterator for Lines<'_> {
+  type Item = String;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let line = self.rope.line(self.row)?;
+    self.row += 1;
+    Some(line)
+  }
+}
+
+/// Splits `text` into chunks of at most [MAX_LEAF_BYTES] on grapheme cluster boundaries.
+fn split_into_leaves(text: &str) -> Vec<String> {
+  let mut leaves = vec![];
+  let mut current = String::new();
+  for g_c in text.graphemes(true) {
+    if !current.is_empty() && current.len() + g_c.len() > MAX_LEAF_BYTES {
+      leaves.push(std::mem::take(&mut current));
+    }
+    current.push_str(g_c);
+  }
+  if !current.is_empty() {
+    leaves.push(current);
+  }
+  leaves
+}
+
+/// A display column in the middle of a wide grapheme cluster snaps to the start of the
+/// cluster.
+fn byte_offset_of_display_col(
+  line: &str,
+  display_col: usize,
+) -> usize {
+  let mut width = 0;
+  for (byte_offset, g_c) in line.grapheme_indices(true) {
+    let g_c_width = UnicodeWidthStr::width(g_c);
+    if width + g_c_width > display_col {
+      return byte_offset;
+    }
+    width += g_c_width;
+  }
+  line.len()
+}
+
+#[cfg(test)]
+fn make_test_text(lines: usize) -> String {
+  (0..lines)
+    .map(|index| {
+      format!(
+        "{} line 😃 🇺🇸 cafe\u{301} 👨🏾‍🤝‍👨🏿 {}",
+        index,
+        "x".repeat(index % 7)
+      )
+    })
+    .collect::<Vec<String>>()
+    .join("\n")
+}
+
+/// Checks that the cached depths are right, that the 2 children of every branch differ in
+/// depth by at most 1, and so that the depth is `O(log(leaves))`.
+#[cfg(test)]
+fn assert_balanced(rope: &Rope) {
+  fn check_depth(node: &Node) -> usize {
+    match node {
+      Node::Leaf(_) => 0,
+      Node::Branch {
+        left, right, depth, ..
+      } => {
+        let (left_depth, right_depth) = (check_depth(left), check_depth(right));
+        assert!(left_depth.abs_diff(right_depth) <= 1);
+        assert_eq!(*depth, left_depth.max(right_depth) + 1);
+        *depth
+      }
+    }
+  }
+  let depth = check_depth(&rope.root);
+  let leaves = rope.get_leaves().len() as f64;
+  assert!(depth <= 2 * leaves.log2().ceil() as usize + 2);
+}
+
+/// If a leaf split a grapheme cluster, the clusters in each leaf wouldn't match the
+/// clusters in the whole text.
+#[cfg(test)]
+fn assert_grapheme_aligned(rope: &Rope) {
+  let text = rope.to_string();
+  let graphemes_by_leaf = rope
+    .get_leaves()
+    .iter()
+    .flat_map(|leaf| leaf.graphemes(true))
+    .collect::<Vec<&str>>();
+  assert_eq!(
+    graphemes_by_leaf,
+    text.graphemes(true).collect::<Vec<&str>>()
+  );
+}
+
+#[test]
+fn test_rope_leaves_are_grapheme_aligned() {
+  let text = make_test_text(200);
+  let rope = Rope::from(text.as_str());
+  let leaves = rope.get_leaves();
+
+  assert!(leaves.len() > 1);
+  assert!(leaves.iter().all(|leaf| leaf.len() <= MAX_LEAF_BYTES));
+  assert_grapheme_aligned(&rope);
+  assert_eq!(rope.to_string(), text);
+  assert_eq!(rope.len_bytes(), text.len());
+}
+
+#[test]
+fn test_rope_edits_at_leaf_seams_stay_grapheme_aligned() {
+  let text = "e".repeat(MAX_LEAF_BYTES * 2);
+  let mut expected = text.clone();
+  let mut rope = Rope::from(text.as_str());
+  let seam = rope.get_leaves()[0].len();
+
+  // A combining accent right after the `e` at the end of the first leaf.
+  rope.insert_at_byte(seam, "\u{301}");
+  expected.insert(seam, '\u{301}');
+  assert_eq!(rope.to_string(), expected);
+  assert_grapheme_aligned(&rope);
+
+  // Deleting the text between the 2 halves of a cluster joins them.
+  let mut rope = Rope::from(format!("{}XYZ\u{301}\u{302}ab", text).as_str());
+  let start = text.len();
+  rope.delete_bytes(start..start + 3);
+  assert_eq!(rope.to_string(), format!("{}\u{301}\u{302}ab", text));
+  assert_grapheme_aligned(&rope);
+
+  // The cluster goes past the end of the first leaf of the right side.
+  let mut rope = Rope::from(text.as_str());
+  for _ in 0..3 {
+    rope.insert_at_byte(MAX_LEAF_BYTES, &"\u{301}".repeat(MAX_LEAF_BYTES));
+  }
+  assert_grapheme_aligned(&rope);
+  assert_eq!(rope.len_bytes(), text.len() + 3 * 2 * MAX_LEAF_BYTES);
+
+  // A flag whose 2 regional indicators end up on either side of a seam.
+  let mut rope = Rope::from(format!("{}🇸ab", text).as_str());
+  rope.insert_at_byte(text.len(), "🇺");
+  assert_eq!(rope.to_string(), format!("{}🇺🇸ab", text));
+  assert_grapheme_aligned(&rope);
+  assert_balanced(&rope);
+}
+
+#[test]
+fn test_rope_line_indexing() {
+  let text = make_test_text(200);
+  let rope = Rope::from(text.as_str());
+
+  assert_eq!(rope.len_lines(), 200);
+  assert_eq!(
+    rope.lines().collect::<Vec<String>>(),
+    text.split('\n').collect::<Vec<&str>>()
+  );
+  assert_eq!(rope.line(150).unwrap(), text.split('\n').nth(150).unwrap());
+  assert_eq!(rope.line(200), None);
+  assert_eq!(rope.line_start_byte(1), Some(text.find('\n').unwrap() + 1));
+
+  let empty = Rope::new();
+  assert_eq!(empty.len_lines(), 1);
+  assert_eq!(empty.line(0), Some(String::new()));
+  assert_eq!(
+    Rope::from("a\n").lines().collect::<Vec<String>>(),
+    vec!["a", ""]
+  );
+}
+
+#[test]
+fn test_rope_edit_by_display_col() {
+  let mut rope = Rope::from("😃abc\n🇺🇸 flag");
+
+  assert_eq!(rope.line_display_width(0), Some(5));
+  assert_eq!(rope.slice(0, 2..4), Some("ab".to_string()));
+  assert_eq!(rope.slice(0, 0..100), Some("😃abc".to_string()));
+
+  assert!(rope.insert(0, 2, "X"));
+  assert_eq!(rope.line(0).unwrap(), "😃Xabc");
+
+  // Col 1 is in the middle of the emoji, so it snaps to col 0.
+  assert!(rope.insert(0, 1, "Y"));
+  assert_eq!(rope.line(0).unwrap(), "Y😃Xabc");
+
+  assert!(rope.delete(1, 0..3));
+  assert_eq!(rope.line(1).unwrap(), "flag");
+
+  // Deleting past the end of a line doesn't join it w/ the next line.
+  assert!(rope.delete(0, 4..100));
+  assert_eq!(rope.to_string(), "Y😃X\nflag");
+
+  assert!(!rope.insert(5, 0, "nope"));
+  assert!(!rope.delete(5, 0..1));
+  assert_eq!(rope.slice(5, 0..1), None);
+}
+
+#[test]
+fn test_rope_matches_string_after_many_edits() {
+  let mut rope = Rope::new();
+  let mut expected = String::new();
+
+  // Inserting at the front over and over would make the tree lopsided if it weren't
+  // rebalanced.
+  for index in 0..2_000 {
+    let text = format!("{}😃\n", index);
+    rope.insert(0, 0, &text);
+    expected.insert_str(0, &text);
+  }
+  assert_balanced(&rope);
+  assert_grapheme_aligned(&rope);
+  assert_eq!(rope.to_string(), expected);
+
+  for _ in 0..500 {
+    let start = rope.line_start_byte(10).unwrap();
+    let end = rope.line_start_byte(11).unwrap();
+    rope.delete_bytes(start..end);
+    expected.replace_range(start..end, "");
+  }
+  assert_balanced(&rope);
+  assert_eq!(rope.to_string(), expected);
+  assert_eq!(rope.len_lines(), expected.split('\n').count());
+}
+
+/// Compares edits in the middle of a large (~4MB) document w/ the same edits on a
+/// [String]. Run w/ `cargo test --release -- --ignored --nocapture bench_rope`.
+#[test]
+#[ignore]
+fn bench_rope_vs_string() {
+  use std::time::{Duration, Instant};
+
+  let text = make_test_text(70_000);
+  let mut rope = Rope::from(text.as_str());
+  let mut string = text.clone();
+  let (mut rope_time, mut string_time) = (Duration::ZERO, Duration::ZERO);
+
+  let middle = rope.line_start_byte(rope.len_lines() / 2).unwrap();
+  for index in 0..2_000 {
+    let line = format!("{} inserted 😃\n", index);
+    let start = Instant::now();
+    rope.insert_at_byte(middle, &line);
+    rope_time += start.elapsed();
+    let start = Instant::now();
+    string.insert_str(middle, &line);
+    string_time += start.elapsed();
+  }
+  println!(
+    "2000 inserts in the middle of {} bytes: rope {:?}, String {:?}",
+    text.len(),
+    rope_time,
+    string_time
+  );
+
+  let (mut rope_time, mut string_time) = (Duration::ZERO, Duration::ZERO);
+  for _ in 0..2_000 {
+    let row = rope.len_lines() / 2;
+    let range =
+      rope.line_start_byte(row).unwrap()..rope.line_start_byte(row + 1).unwrap();
+    let start = Instant::now();
+    rope.delete_bytes(range.clone());
+    rope_time += start.elapsed();
+    let start = Instant::now();
+    string.replace_range(range, "");
+    string_time += start.elapsed();
+  }
+  println!(
+    "2000 line deletes in the middle: rope {:?}, String {:?}",
+    rope_time, string_time
+  );
+
+  assert_balanced(&rope);
+  assert_eq!(rope.to_string(), string);
+}
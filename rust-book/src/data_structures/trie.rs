@@ -0,0 +1,279 @@
+/*
+ Copyright 2022 Nazmul Idris
+
+ Licensed under the Apache License, Version 2.0 (the "License");
+ you may not use this file except in compliance with the License.
+ You may obtain a copy of the License at
+
+      https://www.apache.org/licenses/LICENSE-2.0
+
+ Unless required by applicable law or agreed to in writing, software
+ distributed under the License is distributed on an "AS IS" BASIS,
+ WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ See the License for the specific language governing permissions and
+ limitations under the License.
+*/
+
+//! # Trie (prefix tree)
+//! ----------------------------------------------------------------------------
+//! Each edge in the [Trie] is a grapheme cluster (a user perceived character) rather than
+//! a byte or a `char`. So a prefix never ends in the middle of a cluster, eg: `"cafe"` is
+//! not a prefix of `"cafe\u{301}"` (café w/ a combining accent), and `"🇺"` is not a
+//! prefix of `"🇺🇸"`.
+//!
+//! Each word has a frequency (how many times it was inserted), which is used to rank
+//! suggestions for autocomplete.
+//!
+//! - Trie: <https://en.wikipedia.org/wiki/Trie>
+//! - Grapheme clusters: <https://unicode.org/reports/tr29/>
+
+use std::collections::HashMap;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+pub fn run() {}
+
+#[derive(Debug, Default)]
+struct TrieNode {
+  children: HashMap<String, TrieNode>,
+  /// 0 means that no word ends at this node.
+  frequency: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct Trie {
+  root: TrieNode,
+  len: usize,
+}
+
+impl Trie {
+  pub fn new() -> Self {
+    Trie::default()
+  }
+
+  /// Number of distinct words.
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  /// Inserting a word that already exists increases its frequency by 1.
+  pub fn insert(
+    &mut self,
+    word: &str,
+  ) {
+    self.insert_with_frequency(word, 1);
+  }
+
+  pub fn insert_with_frequency(
+    &mut self,
+    word: &str,
+    frequency: usize,
+  ) {
+    if word.is_empty() || frequency == 0 {
+      return;
+    }
+    let mut node = &mut self.root;
+    for g_c in word.graphemes(true) {
+      node = node.children.entry(g_c.to_string()).or_default();
+    }
+    if node.frequency == 0 {
+      self.len += 1;
+    }
+    node.frequency += frequency;
+  }
+
+  pub fn get_frequency(
+    &self,
+    word: &str,
+  ) -> usize {
+    self.find_node(word).map_or(0, |node| node.frequency)
+  }
+
+  pub fn contains(
+    &self,
+    word: &str,
+  ) -> bool {
+    self.get_frequency(word) > 0
+  }
+
+  pub fn starts_with(
+    &self,
+    prefix: &str,
+  ) -> bool {
+    self.find_node(prefix).is_some()
+  }
+
+  /// All the words that start w/ `prefix` (including `prefix` itself), sorted
+  /// alphabetically.
+  pub fn words_with_prefix(
+    &self,
+    prefix: &str,
+  ) -> Vec<String> {
+    let mut words = self
+      .collect_words(prefix)
+      .into_iter()
+      .map(|(word, _)| word)
+      .collect::<Vec<String>>();
+    words.sort();
+    words
+  }
+
+  /// Up to `limit` words that start w/ `prefix`, most frequent first (ties are sorted
+  /// alphabetically), along w/ their frequencies.
+  pub fn suggest(
+    &self,
+    prefix: &str,
+    limit: usize,
+  ) -> Vec<(String, usize)> {
+    let mut suggestions = self.collect_words(prefix);
+    suggestions.sort_by(|(word_a, frequency_a), (word_b, frequency_b)| {
+      frequency_b.cmp(frequency_a).then(word_a.cmp(word_b))
+    });
+    suggestions.truncate(limit);
+    suggestions
+  }
+
+  /// Returns false if the word isn't in the trie. Nodes that are no longer needed are
+  /// pruned.
+  pub fn remove(
+    &mut self,
+    word: &str,
+  ) -> bool {
+    let graphemes = word.graphemes(true).collect::<Vec<&str>>();
+    if Trie::remove_recursive(&mut self.root, &graphemes) {
+      self.len -= 1;
+      true
+    } else {
+      false
+    }
+  }
+
+  /// Returns true if the word was removed.
+  fn remove_recursive(
+    node: &mut TrieNode,
+    graphemes: &[&str],
+  ) -> bool {
+    match graphemes.split_first() {
+      None => {
+        let was_word = node.frequency > 0;
+        node.frequency = 0;
+        was_word
+      }
+      Some((g_c, rest)) => {
+        let child = match node.children.get_mut(*g_c) {
+          Some(child) => child,
+          None => return false,
+        };
+        let removed = Trie::remove_recursive(child, rest);
+        if removed && child.frequency == 0 && child.children.is_empty() {
+          node.children.remove(*g_c);
+        }
+        removed
+      }
+    }
+  }
+
+  fn find_node(
+    &self,
+    prefix: &str,
+  ) -> Option<&TrieNode> {
+    let mut node = &self.root;
+    for g_c in prefix.graphemes(true) {
+      node = node.children.get(g_c)?;
+    }
+    Some(node)
+  }
+
+  fn collect_words(
+    &self,
+    prefix: &str,
+  ) -> Vec<(String, usize)> {
+    let mut words = vec![];
+    if let Some(node) = self.find_node(prefix) {
+      let mut stack = vec![(prefix.to_string(), node)];
+      while let Some((word, node)) = stack.pop() {
+        if node.frequency > 0 {
+          words.push((word.clone(), node.frequency));
+        }
+        for (g_c, child) in &node.children {
+          stack.push((format!("{}{}", word, g_c), child));
+        }
+      }
+    }
+    words
+  }
+}
+
+#[test]
+fn test_trie_insert_and_search() {
+  let mut trie = Trie::new();
+  for word in ["add", "add-async", "remove", "search", "add"] {
+    trie.insert(word);
+  }
+  assert_eq!(trie.len(), 4);
+  assert!(trie.contains("add"));
+  assert!(!trie.contains("ad"));
+  assert!(trie.starts_with("ad"));
+  assert!(!trie.starts_with("x"));
+  assert_eq!(trie.get_frequency("add"), 2);
+  assert_eq!(trie.words_with_prefix("a"), vec!["add", "add-async"]);
+  assert!(trie.words_with_prefix("z").is_empty());
+}
+
+#[test]
+fn test_trie_is_keyed_by_grapheme_clusters() {
+  let mut trie = Trie::new();
+  trie.insert("cafe\u{301}"); // café w/ a combining accent.
+  trie.insert("🇺🇸 flag");
+  trie.insert("👨🏾‍🤝‍👨🏿 people");
+
+  // A prefix can't end in the middle of a grapheme cluster.
+  assert!(!trie.starts_with("cafe"));
+  assert!(trie.starts_with("caf"));
+  assert!(!trie.starts_with("🇺"));
+  assert!(trie.starts_with("🇺🇸"));
+  assert!(!trie.starts_with("👨🏾"));
+  assert_eq!(trie.words_with_prefix("👨🏾‍🤝‍👨🏿"), vec!["👨🏾‍🤝‍👨🏿 people"]);
+}
+
+#[test]
+fn test_trie_suggestions_are_ranked_by_frequency() {
+  let mut trie = Trie::new();
+  trie.insert_with_frequency("search", 3);
+  trie.insert_with_frequency("select", 10);
+  trie.insert_with_frequency("send", 3);
+  trie.insert_with_frequency("quit", 50);
+
+  assert_eq!(
+    trie.suggest("se", 2),
+    vec![("select".to_string(), 10), ("search".to_string(), 3)]
+  );
+  assert_eq!(
+    trie.suggest("se", 10),
+    vec![
+      ("select".to_string(), 10),
+      ("search".to_string(), 3),
+      ("send".to_string(), 3)
+    ]
+  );
+}
+
+#[test]
+fn test_trie_remove_prunes_nodes() {
+  let mut trie = Trie::new();
+  trie.insert("add");
+  trie.insert("add-async");
+
+  assert!(trie.remove("add-async"));
+  assert!(!trie.remove("add-async"));
+  assert!(!trie.starts_with("add-"));
+  assert!(trie.contains("add"));
+
+  assert!(trie.remove("add"));
+  assert!(trie.is_empty());
+  assert!(!trie.starts_with("a"));
+}
@@ -24,3 +24,4 @@ pub mod smart_pointers;
 pub mod builders;
 pub mod oop;
 pub mod function_pointers;
+pub mod state_machine;
@@ -0,0 +1,335 @@
+/*
+ Copyright 2022 Nazmul Idris
+
+ Licensed under the Apache License, Version 2.0 (the "License");
+ you may not use this file except in compliance with the License.
+ You may obtain a copy of the License at
+
+      https://www.apache.org/licenses/LICENSE-2.0
+
+ Unless required by applicable law or agreed to in writing, software
+ distributed under the License is distributed on an "AS IS" BASIS,
+ WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ See the License for the specific language governing permissions and
+ limitations under the License.
+*/
+
+//! # Finite state machine (FSM)
+//! ----------------------------------------------------------------------------
+//! A [StateMachine] is made using a [StateMachineBuilder] (consuming builder). It is generic over:
+//! 1. `S` - the states (usually a fieldless enum).
+//! 2. `E` - the events that cause transitions (usually a fieldless enum).
+//! 3. `C` - a context that guards can read, and entry / exit hooks can mutate.
+//!
+//! When an event is fired, the transitions registered for the current state & event are checked in
+//! the order that they were added, and the first one whose guard passes (or that has no guard) is
+//! taken. The exit hooks of the current state run, then the entry hooks of the next state.
+//!
+//! - State pattern in the Rust book:
+//!   <https://doc.rust-lang.org/book/ch17-03-oo-design-patterns.html>
+//! - Builders: <https://doc.rust-lang.org/1.0.0/style/ownership/builders.html>
+
+use std::{collections::HashMap, fmt::Debug, hash::Hash};
+
+pub fn run() {}
+
+type Guard<C> = Box<dyn Fn(&C) -> bool>;
+type Hook<C> = Box<dyn Fn(&mut C)>;
+
+struct Transition<S, C> {
+  to: S,
+  guard: Option<Guard<C>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransitionError<S, E> {
+  /// No transition is registered for this state & event.
+  NoTransition { state: S, event: E },
+  /// Transitions are registered for this state & event, but all their guards rejected it.
+  GuardRejected { state: S, event: E },
+}
+
+pub struct StateMachineBuilder<S, E, C> {
+  initial_state: S,
+  transitions: HashMap<(S, E), Vec<Transition<S, C>>>,
+  on_entry: HashMap<S, Vec<Hook<C>>>,
+  on_exit: HashMap<S, Vec<Hook<C>>>,
+}
+
+impl<S, E, C> StateMachineBuilder<S, E, C>
+where
+  S: Copy + Eq + Hash + Debug,
+  E: Copy + Eq + Hash + Debug,
+{
+  pub fn new(initial_state: S) -> Self {
+    StateMachineBuilder {
+      initial_state,
+      transitions: HashMap::new(),
+      on_entry: HashMap::new(),
+      on_exit: HashMap::new(),
+    }
+  }
+
+  pub fn transition(
+    self,
+    from: S,
+    event: E,
+    to: S,
+  ) -> Self {
+    self.add_transition(from, event, to, None)
+  }
+
+  /// The transition is only taken if `guard` returns true for the current context.
+  pub fn guarded_transition(
+    self,
+    from: S,
+    event: E,
+    to: S,
+    guard: impl Fn(&C) -> bool + 'static,
+  ) -> Self {
+    self.add_transition(from, event, to, Some(Box::new(guard)))
+  }
+
+  pub fn on_entry(
+    mut self,
+    state: S,
+    hook: impl Fn(&mut C) + 'static,
+  ) -> Self {
+    self.on_entry.entry(state).or_default().push(Box::new(hook));
+    self
+  }
+
+  pub fn on_exit(
+    mut self,
+    state: S,
+    hook: impl Fn(&mut C) + 'static,
+  ) -> Self {
+    self.on_exit.entry(state).or_default().push(Box::new(hook));
+    self
+  }
+
+  /// The entry hooks of the initial state are not run.
+  pub fn build(
+    self,
+    context: C,
+  ) -> StateMachine<S, E, C> {
+    StateMachine {
+      state: self.initial_state,
+      context,
+      transitions: self.transitions,
+      on_entry: self.on_entry,
+      on_exit: self.on_exit,
+    }
+  }
+
+  fn add_transition(
+    mut self,
+    from: S,
+    event: E,
+    to: S,
+    guard: Option<Guard<C>>,
+  ) -> Self {
+    self
+      .transitions
+      .entry((from, event))
+      .or_default()
+      .push(Transition { to, guard });
+    self
+  }
+}
+
+pub struct StateMachine<S, E, C> {
+  state: S,
+  context: C,
+  transitions: HashMap<(S, E), Vec<Transition<S, C>>>,
+  on_entry: HashMap<S, Vec<Hook<C>>>,
+  on_exit: HashMap<S, Vec<Hook<C>>>,
+}
+
+impl<S, E, C> StateMachine<S, E, C>
+where
+  S: Copy + Eq + Hash + Debug,
+  E: Copy + Eq + Hash + Debug,
+{
+  pub fn get_state(&self) -> S {
+    self.state
+  }
+
+  pub fn get_context(&self) -> &C {
+    &self.context
+  }
+
+  pub fn get_context_mut(&mut self) -> &mut C {
+    &mut self.context
+  }
+
+  /// Returns the new state if a transition was taken. The state doesn't change if an error is
+  /// returned.
+  pub fn fire(
+    &mut self,
+    event: E,
+  ) -> Result<S, TransitionError<S, E>> {
+    let state = self.state;
+    let transitions = match self.transitions.get(&(state, event)) {
+      Some(transitions) => transitions,
+      None => return Err(TransitionError::NoTransition { state, event }),
+    };
+
+    let context = &self.context;
+    let next_state = transitions
+      .iter()
+      .find(|transition| match &transition.guard {
+        Some(guard) => guard(context),
+        None => true,
+      })
+      .map(|transition| transition.to)
+      .ok_or(TransitionError::GuardRejected { state, event })?;
+
+    if let Some(hooks) = self.on_exit.get(&state) {
+      hooks.iter().for_each(|hook| hook(&mut self.context));
+    }
+    self.state = next_state;
+    if let Some(hooks) = self.on_entry.get(&next_state) {
+      hooks.iter().for_each(|hook| hook(&mut self.context));
+    }
+
+    Ok(next_state)
+  }
+}
+
+/// Modal editing modes (like vi) for a text editor component.
+#[test]
+fn test_modal_editing_modes() {
+  #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+  enum Mode {
+    Normal,
+    Insert,
+    Visual,
+    Command,
+  }
+
+  #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+  enum Key {
+    I,
+    V,
+    Colon,
+    Esc,
+    Enter,
+  }
+
+  #[derive(Debug, Default)]
+  struct Editor {
+    read_only: bool,
+    insert_sessions: usize,
+    log: Vec<String>,
+  }
+
+  let mut fsm = StateMachineBuilder::new(Mode::Normal)
+    .guarded_transition(Mode::Normal, Key::I, Mode::Insert, |editor: &Editor| {
+      !editor.read_only
+    })
+    .transition(Mode::Normal, Key::V, Mode::Visual)
+    .transition(Mode::Normal, Key::Colon, Mode::Command)
+    .transition(Mode::Insert, Key::Esc, Mode::Normal)
+    .transition(Mode::Visual, Key::Esc, Mode::Normal)
+    .transition(Mode::Command, Key::Esc, Mode::Normal)
+    .transition(Mode::Command, Key::Enter, Mode::Normal)
+    .on_entry(Mode::Insert, |editor| editor.insert_sessions += 1)
+    .on_entry(Mode::Insert, |editor| {
+      editor.log.push("enter insert".to_string())
+    })
+    .on_exit(Mode::Insert, |editor| {
+      editor.log.push("exit insert".to_string())
+    })
+    .build(Editor::default());
+
+  assert_eq!(fsm.get_state(), Mode::Normal);
+
+  assert_eq!(fsm.fire(Key::I), Ok(Mode::Insert));
+  assert_eq!(
+    fsm.fire(Key::V),
+    Err(TransitionError::NoTransition {
+      state: Mode::Insert,
+      event: Key::V
+    })
+  );
+  assert_eq!(fsm.fire(Key::Esc), Ok(Mode::Normal));
+  assert_eq!(fsm.get_context().insert_sessions, 1);
+  assert_eq!(fsm.get_context().log, vec!["enter insert", "exit insert"]);
+
+  // Guard rejects entering insert mode when the editor is read only.
+  fsm.get_context_mut().read_only = true;
+  assert_eq!(
+    fsm.fire(Key::I),
+    Err(TransitionError::GuardRejected {
+      state: Mode::Normal,
+      event: Key::I
+    })
+  );
+  assert_eq!(fsm.get_state(), Mode::Normal);
+  assert_eq!(fsm.get_context().insert_sessions, 1);
+
+  assert_eq!(fsm.fire(Key::Colon), Ok(Mode::Command));
+  assert_eq!(fsm.fire(Key::Enter), Ok(Mode::Normal));
+}
+
+/// Lifecycle of a REPL: it starts, runs commands, and exits (possibly w/ a confirmation if there
+/// are unsaved changes).
+#[test]
+fn test_repl_lifecycle() {
+  #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+  enum Lifecycle {
+    Starting,
+    Running,
+    ConfirmExit,
+    Exited,
+  }
+
+  #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+  enum Event {
+    Ready,
+    Command,
+    Quit,
+    Yes,
+    No,
+  }
+
+  #[derive(Debug, Default)]
+  struct Repl {
+    has_unsaved_changes: bool,
+    commands_run: usize,
+    said_goodbye: bool,
+  }
+
+  // When there are unsaved changes, quitting needs to be confirmed. The guarded transitions for
+  // `Quit` are checked in order.
+  let mut fsm = StateMachineBuilder::new(Lifecycle::Starting)
+    .transition(Lifecycle::Starting, Event::Ready, Lifecycle::Running)
+    .transition(Lifecycle::Running, Event::Command, Lifecycle::Running)
+    .guarded_transition(
+      Lifecycle::Running,
+      Event::Quit,
+      Lifecycle::ConfirmExit,
+      |repl: &Repl| repl.has_unsaved_changes,
+    )
+    .transition(Lifecycle::Running, Event::Quit, Lifecycle::Exited)
+    .transition(Lifecycle::ConfirmExit, Event::Yes, Lifecycle::Exited)
+    .transition(Lifecycle::ConfirmExit, Event::No, Lifecycle::Running)
+    .on_entry(Lifecycle::Running, |repl| repl.commands_run += 1)
+    .on_entry(Lifecycle::Exited, |repl| repl.said_goodbye = true)
+    .build(Repl::default());
+
+  assert_eq!(fsm.fire(Event::Ready), Ok(Lifecycle::Running));
+  assert_eq!(fsm.fire(Event::Command), Ok(Lifecycle::Running));
+  // Self transitions run the exit & entry hooks too.
+  assert_eq!(fsm.get_context().commands_run, 2);
+
+  fsm.get_context_mut().has_unsaved_changes = true;
+  assert_eq!(fsm.fire(Event::Quit), Ok(Lifecycle::ConfirmExit));
+  assert_eq!(fsm.fire(Event::No), Ok(Lifecycle::Running));
+
+  fsm.get_context_mut().has_unsaved_changes = false;
+  assert_eq!(fsm.fire(Event::Quit), Ok(Lifecycle::Exited));
+  assert!(fsm.get_context().said_goodbye);
+  assert!(fsm.fire(Event::Command).is_err());
+}
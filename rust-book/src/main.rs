@@ -23,6 +23,7 @@
 // Attach the following files to the binary module.
 mod basics; // This is a module that contains many other files.
 mod concurrency;
+mod contacts; // This is a module that contains many other files.
 mod data_structures; // This is a module that contains many other files.
 mod intermediate; // This is a module that contains many other files.
 mod runnable; // This is a module that contains many other files.
@@ -45,6 +46,9 @@ fn main() {
   data_structures::strings::run();
   data_structures::hashmap::run();
   data_structures::tree::run();
+  data_structures::priority_queue::run();
+  data_structures::rope::run();
+  data_structures::trie::run();
 
   intermediate::error_handling::run();
   intermediate::generic_types::run();
@@ -56,8 +60,13 @@ fn main() {
   intermediate::builders::run();
   intermediate::oop::run();
   intermediate::function_pointers::run();
+  intermediate::state_machine::run();
 
   concurrency::threads::run();
   concurrency::message_passing::run();
   concurrency::shared_state::run();
+
+  contacts::email::run();
+  contacts::faker::run();
+  contacts::phone::run();
 }
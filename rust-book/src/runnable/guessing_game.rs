@@ -15,8 +15,19 @@
  */
 
 //! Rust book: <https://doc.rust-lang.org/book/ch02-00-guessing-game-tutorial.html>
+//!
+//! Extended w/:
+//! 1. [Difficulty] selection, which sets the range of the random number & max attempts.
+//! 2. [GameStats] (games played, won, average guesses) that are persisted between runs to a
+//!    stats file in the user's config directory, using a simple `key=value` per line format.
+//! 3. A summary table that is printed at the end of each game.
 
-use std::cmp::Ordering;
+use std::{
+  cmp::Ordering,
+  env, fs, io,
+  ops::RangeInclusive,
+  path::{Path, PathBuf},
+};
 
 use ansi_term::Colour::Blue;
 use rand::{thread_rng, Rng};
@@ -24,23 +35,240 @@ use r3bl_rs_utils::utils::{
   print_header, readline, style_dimmed, style_error, style_primary, style_prompt,
 };
 
+const STATS_DIR_NAME: &str = "rust_book";
+const STATS_FILE_NAME: &str = "guessing_game_stats.txt";
+
 pub fn run() {
   print_header("guessing_game");
   println!("Guess the number game :)");
-  let answer: u32 = gen_rand_num();
+
+  let difficulty = select_difficulty();
+  let answer: u32 = gen_rand_num(&difficulty);
   println!("The random number is: {}", answer);
 
-  loop {
-    let guess: String = make_a_guess();
-    match guess.as_str().cmp("quit") {
-      Ordering::Equal => {
-        break;
+  let outcome = play(&difficulty, &answer);
+  let stats = record_stats(&outcome);
+  print_summary_table(&difficulty, &outcome, &stats);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+  Easy,
+  Medium,
+  Hard,
+}
+
+impl Difficulty {
+  /// Accepts the number or the name of the difficulty (case insensitive).
+  pub fn parse(input: &str) -> Option<Difficulty> {
+    match input.trim().to_lowercase().as_str() {
+      "1" | "easy" => Some(Difficulty::Easy),
+      "2" | "medium" => Some(Difficulty::Medium),
+      "3" | "hard" => Some(Difficulty::Hard),
+      _ => None,
+    }
+  }
+
+  pub fn range(&self) -> RangeInclusive<u32> {
+    match self {
+      Difficulty::Easy => 1..=10,
+      Difficulty::Medium => 1..=50,
+      Difficulty::Hard => 1..=100,
+    }
+  }
+
+  pub fn max_attempts(&self) -> u32 {
+    match self {
+      Difficulty::Easy => 5,
+      Difficulty::Medium => 7,
+      Difficulty::Hard => 8,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameOutcome {
+  Won { guesses: u32 },
+  Lost { guesses: u32 },
+  Quit,
+}
+
+/// Stats across all the games that have been played.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GameStats {
+  pub games_played: u32,
+  pub games_won: u32,
+  /// Sum of the guesses it took to win each game that was won.
+  pub total_winning_guesses: u32,
+}
+
+impl GameStats {
+  /// Games that were quit are not counted.
+  pub fn record(
+    &mut self,
+    outcome: &GameOutcome,
+  ) {
+    match outcome {
+      GameOutcome::Won { guesses } => {
+        self.games_played += 1;
+        self.games_won += 1;
+        self.total_winning_guesses += guesses;
       }
-      _ => {
-        match_guess(&answer, &guess);
+      GameOutcome::Lost { .. } => self.games_played += 1,
+      GameOutcome::Quit => {}
+    }
+  }
+
+  /// Average number of guesses it took to win a game.
+  pub fn average_guesses(&self) -> Option<f64> {
+    match self.games_won {
+      0 => None,
+      games_won => Some(self.total_winning_guesses as f64 / games_won as f64),
+    }
+  }
+
+  pub fn serialize(&self) -> String {
+    format!(
+      "games_played={}\ngames_won={}\ntotal_winning_guesses={}\n",
+      self.games_played, self.games_won, self.total_winning_guesses
+    )
+  }
+
+  /// Unknown keys are skipped (eg: keys written by a newer version of the game).
+  pub fn deserialize(text: &str) -> Result<GameStats, String> {
+    let mut stats = GameStats::default();
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+      let (key, value) = line
+        .split_once('=')
+        .ok_or(format!("Expected `key=value`, got '{}'", line))?;
+      let value = value
+        .trim()
+        .parse::<u32>()
+        .map_err(|err| format!("Invalid value for '{}': {}", key, err))?;
+      match key.trim() {
+        "games_played" => stats.games_played = value,
+        "games_won" => stats.games_won = value,
+        "total_winning_guesses" => stats.total_winning_guesses = value,
+        _ => {}
+      }
+    }
+    Ok(stats)
+  }
+
+  /// Returns empty stats if the file doesn't exist yet, and an error if it can't be read or
+  /// parsed.
+  pub fn load(path: &Path) -> Result<GameStats, String> {
+    match fs::read_to_string(path) {
+      Ok(text) => GameStats::deserialize(&text)
+        .map_err(|err| format!("{}: {}", path.display(), err)),
+      Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(GameStats::default()),
+      Err(err) => Err(format!("{}: {}", path.display(), err)),
+    }
+  }
+
+  /// Creates the parent folder of `path` if needed.
+  pub fn save(
+    &self,
+    path: &Path,
+  ) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    fs::write(path, self.serialize())
+  }
+}
+
+/// `$XDG_CONFIG_HOME/rust_book/`, `%APPDATA%\rust_book\` on Windows, or
+/// `~/.config/rust_book/`. Returns `None` if none of these env vars are set.
+fn get_stats_file_path() -> Option<PathBuf> {
+  let get_env_var = |key: &str| env::var_os(key).filter(|value| !value.is_empty());
+  let config_dir = get_env_var("XDG_CONFIG_HOME")
+    .map(PathBuf::from)
+    .or_else(|| get_env_var("APPDATA").map(PathBuf::from))
+    .or_else(|| get_env_var("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+  Some(config_dir.join(STATS_DIR_NAME).join(STATS_FILE_NAME))
+}
+
+/// Adds `outcome` to the persisted stats, and returns them. If the stats file can't be
+/// loaded, it is left alone (rather than being overwritten w/ empty stats), and the returned
+/// stats only count this game.
+fn record_stats(outcome: &GameOutcome) -> GameStats {
+  let mut this_game_stats = GameStats::default();
+  this_game_stats.record(outcome);
+
+  let stats_file_path = match get_stats_file_path() {
+    Some(path) => path,
+    None => {
+      println!(
+        "{}",
+        style_error("Could not find a config folder, stats won't be saved.")
+      );
+      return this_game_stats;
+    }
+  };
+
+  let mut stats = match GameStats::load(&stats_file_path) {
+    Ok(stats) => stats,
+    Err(err) => {
+      println!(
+        "{}: {}",
+        style_error("Could not load stats, they won't be saved"),
+        err
+      );
+      return this_game_stats;
+    }
+  };
+
+  stats.record(outcome);
+  if let Err(err) = stats.save(&stats_file_path) {
+    println!("{}: {}", style_error("Could not save stats"), err);
+  }
+  stats
+}
+
+fn select_difficulty() -> Difficulty {
+  println!(
+    "{}",
+    Blue.paint("Select difficulty: 1) easy, 2) medium, 3) hard")
+  );
+  let (_, input) = readline();
+  match Difficulty::parse(&input) {
+    Some(difficulty) => difficulty,
+    None => {
+      println!(
+        "{}",
+        style_error("Invalid difficulty, defaulting to easy.")
+      );
+      Difficulty::Easy
+    }
+  }
+}
+
+/// Keeps asking for guesses until the answer is guessed, the max attempts are used up, or the
+/// user types `quit`.
+fn play(
+  difficulty: &Difficulty,
+  answer: &u32,
+) -> GameOutcome {
+  let mut guesses = 0;
+  while guesses < difficulty.max_attempts() {
+    println!(
+      "{} {}",
+      style_dimmed("Attempts remaining:"),
+      style_primary(&(difficulty.max_attempts() - guesses).to_string())
+    );
+    let guess: String = make_a_guess();
+    if guess == "quit" {
+      return GameOutcome::Quit;
+    }
+    if let Some(ordering) = match_guess(answer, &guess) {
+      guesses += 1;
+      if ordering == Ordering::Equal {
+        return GameOutcome::Won { guesses };
       }
     }
   }
+  GameOutcome::Lost { guesses }
 }
 
 /// String not &str due to "struct lifetime" - <https://stackoverflow.com/a/29026565/2085356>
@@ -57,29 +285,142 @@ fn make_a_guess() -> String {
   guess
 }
 
-fn match_guess(answer: &u32, guess: &String) {
+/// Invalid input doesn't count as a guess, so `None` is returned.
+fn match_guess(
+  answer: &u32,
+  guess: &String,
+) -> Option<Ordering> {
   // <https://learning-rust.github.io/docs/e4.unwrap_and_expect.html>
   match guess.parse::<u32>() {
     // <https://techblog.tonsser.com/posts/what-is-rusts-turbofish>
-    Ok(value) => perform_match(answer, &value),
+    Ok(value) => Some(perform_match(answer, &value)),
     Err(_) => {
       println!(
         "{}",
         style_error("Invalid input, must be a number, try again.")
-      )
+      );
+      None
     }
   }
 }
 
-fn perform_match(answer: &u32, value: &u32) {
-  let resp: &str = match value.cmp(answer) {
+fn perform_match(
+  answer: &u32,
+  value: &u32,
+) -> Ordering {
+  let ordering = value.cmp(answer);
+  let resp: &str = match ordering {
     Ordering::Less => "too small",
     Ordering::Equal => "You win",
     Ordering::Greater => "Too big",
   };
-  println!("Your guess is {}", style_prompt(resp))
+  println!("Your guess is {}", style_prompt(resp));
+  ordering
 }
 
-fn gen_rand_num() -> u32 {
-  thread_rng().gen_range(1..11)
+fn gen_rand_num(difficulty: &Difficulty) -> u32 {
+  thread_rng().gen_range(difficulty.range())
+}
+
+fn print_summary_table(
+  difficulty: &Difficulty,
+  outcome: &GameOutcome,
+  stats: &GameStats,
+) {
+  let result = match outcome {
+    GameOutcome::Won { guesses } => format!("won in {} guess(es)", guesses),
+    GameOutcome::Lost { guesses } => format!("lost after {} guess(es)", guesses),
+    GameOutcome::Quit => "quit".to_string(),
+  };
+  let average_guesses = match stats.average_guesses() {
+    Some(average) => format!("{:.2}", average),
+    None => "-".to_string(),
+  };
+  let rows = vec![
+    ("difficulty", format!("{:?}", difficulty)),
+    ("this game", result),
+    ("games played", stats.games_played.to_string()),
+    ("games won", stats.games_won.to_string()),
+    ("average guesses", average_guesses),
+  ];
+
+  println!("{}", style_prompt("┌─────────────────┬──────────────────────────┐"));
+  for (label, value) in rows {
+    println!(
+      "{} {} {} {} {}",
+      style_prompt("│"),
+      style_dimmed(&format!("{:<15}", label)),
+      style_prompt("│"),
+      style_primary(&format!("{:<24}", value)),
+      style_prompt("│")
+    );
+  }
+  println!("{}", style_prompt("└─────────────────┴──────────────────────────┘"));
+}
+
+#[test]
+fn test_difficulty_parse() {
+  assert_eq!(Difficulty::parse("1"), Some(Difficulty::Easy));
+  assert_eq!(Difficulty::parse(" Medium "), Some(Difficulty::Medium));
+  assert_eq!(Difficulty::parse("HARD"), Some(Difficulty::Hard));
+  assert_eq!(Difficulty::parse("impossible"), None);
+  assert_eq!(Difficulty::Hard.range(), 1..=100);
+  assert_eq!(Difficulty::Easy.max_attempts(), 5);
+}
+
+#[test]
+fn test_game_stats_record() {
+  let mut stats = GameStats::default();
+  assert_eq!(stats.average_guesses(), None);
+
+  stats.record(&GameOutcome::Won { guesses: 3 });
+  stats.record(&GameOutcome::Won { guesses: 4 });
+  stats.record(&GameOutcome::Lost { guesses: 5 });
+  stats.record(&GameOutcome::Quit);
+
+  assert_eq!(stats.games_played, 3);
+  assert_eq!(stats.games_won, 2);
+  assert_eq!(stats.average_guesses(), Some(3.5));
+}
+
+#[test]
+fn test_game_stats_serialization_round_trip() {
+  let stats = GameStats {
+    games_played: 10,
+    games_won: 7,
+    total_winning_guesses: 30,
+  };
+  assert_eq!(GameStats::deserialize(&stats.serialize()), Ok(stats));
+  assert!(GameStats::deserialize("games_played=ten").is_err());
+  assert!(GameStats::deserialize("no equals sign").is_err());
+  assert_eq!(
+    GameStats::deserialize("games_played=5\nunknown_key=1\n"),
+    Ok(GameStats {
+      games_played: 5,
+      ..GameStats::default()
+    })
+  );
+}
+
+#[test]
+fn test_game_stats_save_and_load() {
+  let dir = env::temp_dir().join("rust_book_guessing_game_stats_test");
+  let path = dir.join(STATS_FILE_NAME);
+  let _ = fs::remove_dir_all(&dir);
+  assert_eq!(GameStats::load(&path), Ok(GameStats::default()));
+
+  let stats = GameStats {
+    games_played: 2,
+    games_won: 1,
+    total_winning_guesses: 4,
+  };
+  stats.save(&path).unwrap();
+  assert_eq!(GameStats::load(&path), Ok(stats));
+
+  // A corrupt file is reported, not silently replaced w/ empty stats.
+  fs::write(&path, "games_played=lots").unwrap();
+  assert!(GameStats::load(&path).is_err());
+  assert_eq!(fs::read_to_string(&path).unwrap(), "games_played=lots");
+
+  fs::remove_dir_all(&dir).unwrap();
 }
@@ -0,0 +1,116 @@
+use std::{env, fmt::Display};
+
+/// Command line flag that turns off colorized output.
+pub const NO_COLOR_FLAG: &str = "--no-color";
+
+/// Environment variable that turns off colorized output. More info: <https://no-color.org/>
+pub const NO_COLOR_ENV_VAR: &str = "NO_COLOR";
+
+/// Decides whether styled output (from the `style_*` functions) keeps its ANSI escape
+/// codes or gets them stripped before being printed.
+///
+/// Colors are turned off when any of the following is true:
+/// 1. The `--no-color` flag is passed.
+/// 2. The `NO_COLOR` environment variable is set to a non empty value.
+/// 3. `stdout` is not a tty (eg: output is piped into another program or a file).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorPolicy {
+  Colored,
+  Plain,
+}
+
+impl ColorPolicy {
+  /// Removes the `--no-color` flag (if present) from `args` so that the options
+  /// builders never see it, and returns the policy detected from the flag, the
+  /// environment, and `stdout`.
+  pub fn extract_from_args(args: Vec<String>) -> (Vec<String>, ColorPolicy) {
+    let no_color_flag = args.iter().any(|arg| arg == NO_COLOR_FLAG);
+    let args = args
+      .into_iter()
+      .filter(|arg| arg != NO_COLOR_FLAG)
+      .collect::<Vec<String>>();
+    let policy = ColorPolicy::detect(
+      no_color_flag,
+      env::var(NO_COLOR_ENV_VAR).ok(),
+      atty::is(atty::Stream::Stdout),
+    );
+    (args, policy)
+  }
+
+  pub fn detect(
+    no_color_flag: bool,
+    no_color_env_var: Option<String>,
+    is_stdout_tty: bool,
+  ) -> ColorPolicy {
+    let no_color_env_var_set = match no_color_env_var {
+      Some(value) => !value.is_empty(),
+      None => false,
+    };
+    match no_color_flag || no_color_env_var_set || !is_stdout_tty {
+      true => ColorPolicy::Plain,
+      false => ColorPolicy::Colored,
+    }
+  }
+
+  /// Formats the (possibly styled) `it` and strips any ANSI escape codes from it if this
+  /// policy is [ColorPolicy::Plain].
+  pub fn paint(
+    &self,
+    it: impl Display,
+  ) -> String {
+    let text = format!("{}", it);
+    match self {
+      ColorPolicy::Colored => text,
+      ColorPolicy::Plain => strip_ansi(&text),
+    }
+  }
+}
+
+/// Removes ANSI CSI escape sequences (eg: `\x1b[1;34m`) from `text`. A CSI sequence starts
+/// w/ `ESC [` and ends w/ a byte in the range `@` to `~`.
+pub fn strip_ansi(text: &str) -> String {
+  let mut stripped = String::with_capacity(text.len());
+  let mut chars = text.chars().peekable();
+  while let Some(ch) = chars.next() {
+    if ch == '\x1b' && chars.peek() == Some(&'[') {
+      chars.next(); // Skip the `[`.
+      for it in chars.by_ref() {
+        if ('@'..='~').contains(&it) {
+          break;
+        }
+      }
+      continue;
+    }
+    stripped.push(ch);
+  }
+  stripped
+}
+
+#[test]
+fn test_strip_ansi_removes_escape_codes() {
+  assert_eq!(strip_ansi("\x1b[1;34mfoo\x1b[0m bar"), "foo bar");
+  assert_eq!(strip_ansi("no escape codes"), "no escape codes");
+  assert_eq!(strip_ansi("🦀 \x1b[38;5;208m🦜\x1b[0m"), "🦀 🦜");
+}
+
+#[test]
+fn test_detect_color_policy() {
+  assert_eq!(ColorPolicy::detect(false, None, true), ColorPolicy::Colored);
+  assert_eq!(ColorPolicy::detect(true, None, true), ColorPolicy::Plain);
+  assert_eq!(
+    ColorPolicy::detect(false, Some("1".to_string()), true),
+    ColorPolicy::Plain
+  );
+  assert_eq!(
+    ColorPolicy::detect(false, Some("".to_string()), true),
+    ColorPolicy::Colored
+  );
+  assert_eq!(ColorPolicy::detect(false, None, false), ColorPolicy::Plain);
+}
+
+#[test]
+fn test_paint_strips_codes_only_when_plain() {
+  let styled = "\x1b[1mbold\x1b[0m";
+  assert_eq!(ColorPolicy::Colored.paint(styled), styled);
+  assert_eq!(ColorPolicy::Plain.paint(styled), "bold");
+}
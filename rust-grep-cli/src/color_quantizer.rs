@@ -0,0 +1,282 @@
+use std::{collections::HashMap, env};
+
+/// Terminals that support 24 bit color set this to `truecolor` or `24bit`.
+const COLORTERM_ENV_VAR: &str = "COLORTERM";
+
+/// The RGB values of the 16 ANSI colors. Terminals let users change these, so the
+/// defaults of xterm are used.
+const ANSI_16_PALETTE: [Rgb; 16] = [
+  Rgb(0, 0, 0),
+  Rgb(205, 0, 0),
+  Rgb(0, 205, 0),
+  Rgb(205, 205, 0),
+  Rgb(0, 0, 238),
+  Rgb(205, 0, 205),
+  Rgb(0, 205, 205),
+  Rgb(229, 229, 229),
+  Rgb(127, 127, 127),
+  Rgb(255, 0, 0),
+  Rgb(0, 255, 0),
+  Rgb(255, 255, 0),
+  Rgb(92, 92, 255),
+  Rgb(255, 0, 255),
+  Rgb(0, 255, 255),
+  Rgb(255, 255, 255),
+];
+
+/// The levels of each channel in the 6x6x6 color cube of the 256 color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+/// The colors that the terminal can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorSupport {
+  TrueColor,
+  Ansi256,
+  Ansi16,
+}
+
+impl ColorSupport {
+  pub fn from_env() -> ColorSupport {
+    ColorSupport::detect(env::var(COLORTERM_ENV_VAR).ok(), env::var("TERM").ok())
+  }
+
+  pub fn detect(
+    colorterm_env_var: Option<String>,
+    term_env_var: Option<String>,
+  ) -> ColorSupport {
+    match (colorterm_env_var.as_deref(), term_env_var.as_deref()) {
+      (Some("truecolor" | "24bit"), _) => ColorSupport::TrueColor,
+      (_, Some(term)) if term.contains("256color") => ColorSupport::Ansi256,
+      _ => ColorSupport::Ansi16,
+    }
+  }
+}
+
+/// A color that the terminal can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnsiColor {
+  Rgb(Rgb),
+  /// Index into the 256 color palette.
+  Ansi256(u8),
+  /// Index into the 16 color palette (8 to 15 are the bright colors).
+  Ansi16(u8),
+}
+
+impl AnsiColor {
+  /// The escape code that sets the foreground color, eg: `\x1b[38;5;214m`.
+  pub fn fg_escape_code(&self) -> String {
+    match self {
+      AnsiColor::Rgb(Rgb(r, g, b)) => format!("\x1b[38;2;{};{};{}m", r, g, b),
+      AnsiColor::Ansi256(index) => format!("\x1b[38;5;{}m", index),
+      AnsiColor::Ansi16(index) if *index < 8 => format!("\x1b[{}m", 30 + index),
+      AnsiColor::Ansi16(index) => format!("\x1b[{}m", 90 + index - 8),
+    }
+  }
+}
+
+/// Maps RGB colors to the nearest color that the terminal supports, so that a theme
+/// that uses RGB colors still looks right (rather than getting the wrong colors) when
+/// truecolor isn't available.
+///
+/// 1. The distance between colors is measured in the CIELAB color space (CIE76), which
+///    matches how different 2 colors look to people much better than the distance
+///    between their RGB values.
+/// 2. In [ColorSupport::Ansi256] only the color cube & the grayscale ramp (16 to 255)
+///    are used, since the first 16 colors can be changed by the user's terminal theme.
+/// 3. Each RGB color is only mapped once, the result is cached.
+///
+/// - 256 color palette: <https://en.wikipedia.org/wiki/ANSI_escape_code#8-bit>
+/// - CIELAB: <https://en.wikipedia.org/wiki/CIELAB_color_space>
+pub struct ColorQuantizer {
+  color_support: ColorSupport,
+  /// The palette entries (index & color) to pick from.
+  palette: Vec<(u8, Lab)>,
+  cache: HashMap<Rgb, AnsiColor>,
+}
+
+impl ColorQuantizer {
+  pub fn new(color_support: ColorSupport) -> Self {
+    let palette = match color_support {
+      ColorSupport::TrueColor => vec![],
+      ColorSupport::Ansi256 => (16..=255)
+        .map(|index| (index, Lab::from(get_ansi_256_rgb(index))))
+        .collect(),
+      ColorSupport::Ansi16 => (0..16)
+        .map(|index| (index, Lab::from(ANSI_16_PALETTE[index as usize])))
+        .collect(),
+    };
+    ColorQuantizer {
+      color_support,
+      palette,
+      cache: HashMap::new(),
+    }
+  }
+
+  pub fn quantize(
+    &mut self,
+    rgb: Rgb,
+  ) -> AnsiColor {
+    if self.color_support == ColorSupport::TrueColor {
+      return AnsiColor::Rgb(rgb);
+    }
+    if let Some(ansi_color) = self.cache.get(&rgb) {
+      return *ansi_color;
+    }
+    let lab = Lab::from(rgb);
+    let (index, _) = self
+      .palette
+      .iter()
+      .min_by(|(_, a), (_, b)| lab.distance(a).total_cmp(&lab.distance(b)))
+      .unwrap();
+    let ansi_color = match self.color_support {
+      ColorSupport::Ansi256 => AnsiColor::Ansi256(*index),
+      _ => AnsiColor::Ansi16(*index),
+    };
+    self.cache.insert(rgb, ansi_color);
+    ansi_color
+  }
+
+  pub fn get_cache_len(&self) -> usize {
+    self.cache.len()
+  }
+}
+
+/// The RGB value of an entry in the 256 color palette.
+fn get_ansi_256_rgb(index: u8) -> Rgb {
+  match index {
+    0..=15 => ANSI_16_PALETTE[index as usize],
+    16..=231 => {
+      let index = index - 16;
+      Rgb(
+        CUBE_LEVELS[(index / 36) as usize],
+        CUBE_LEVELS[(index / 6 % 6) as usize],
+        CUBE_LEVELS[(index % 6) as usize],
+      )
+    }
+    232..=255 => {
+      let level = 8 + (index - 232) * 10;
+      Rgb(level, level, level)
+    }
+  }
+}
+
+/// A color in the CIELAB color space (w/ a D65 white point).
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Lab {
+  l: f64,
+  a: f64,
+  b: f64,
+}
+
+impl Lab {
+  /// Squared CIE76 distance (it is only used to compare distances).
+  fn distance(
+    &self,
+    other: &Lab,
+  ) -> f64 {
+    (self.l - other.l).powi(2) + (self.a - other.a).powi(2) + (self.b - other.b).powi(2)
+  }
+}
+
+impl From<Rgb> for Lab {
+  fn from(Rgb(r, g, b): Rgb) -> Self {
+    let to_linear = |channel: u8| {
+      let channel = channel as f64 / 255.0;
+      match channel <= 0.04045 {
+        true => channel / 12.92,
+        false => ((channel + 0.055) / 1.055).powf(2.4),
+      }
+    };
+    let (r, g, b) = (to_linear(r), to_linear(g), to_linear(b));
+    // Linear sRGB to XYZ, relative to the D65 white point.
+    let x = (0.4124 * r + 0.3576 * g + 0.1805 * b) / 0.95047;
+    let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    let z = (0.0193 * r + 0.1192 * g + 0.9505 * b) / 1.08883;
+    let f = |t: f64| match t > 216.0 / 24389.0 {
+      true => t.cbrt(),
+      false => (24389.0 / 27.0 * t + 16.0) / 116.0,
+    };
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+    Lab {
+      l: 116.0 * fy - 16.0,
+      a: 500.0 * (fx - fy),
+      b: 200.0 * (fy - fz),
+    }
+  }
+}
+
+#[test]
+fn test_detect_color_support() {
+  assert_eq!(
+    ColorSupport::detect(Some("truecolor".to_string()), Some("xterm".to_string())),
+    ColorSupport::TrueColor
+  );
+  assert_eq!(
+    ColorSupport::detect(None, Some("xterm-256color".to_string())),
+    ColorSupport::Ansi256
+  );
+  assert_eq!(
+    ColorSupport::detect(None, Some("xterm".to_string())),
+    ColorSupport::Ansi16
+  );
+  assert_eq!(ColorSupport::detect(None, None), ColorSupport::Ansi16);
+}
+
+#[test]
+fn test_quantize_to_256_colors() {
+  let mut quantizer = ColorQuantizer::new(ColorSupport::Ansi256);
+  // Colors that are in the palette map to themselves.
+  assert_eq!(quantizer.quantize(Rgb(255, 0, 0)), AnsiColor::Ansi256(196));
+  assert_eq!(quantizer.quantize(Rgb(0, 0, 0)), AnsiColor::Ansi256(16));
+  assert_eq!(quantizer.quantize(Rgb(128, 128, 128)), AnsiColor::Ansi256(244));
+  for index in 16..=255 {
+    assert_eq!(
+      quantizer.quantize(get_ansi_256_rgb(index)),
+      AnsiColor::Ansi256(index)
+    );
+  }
+  // Orange, and a gray that is closer to the grayscale ramp than to the cube.
+  assert_eq!(quantizer.quantize(Rgb(255, 165, 0)), AnsiColor::Ansi256(214));
+  assert_eq!(quantizer.quantize(Rgb(60, 61, 60)), AnsiColor::Ansi256(237));
+}
+
+#[test]
+fn test_quantize_to_16_colors() {
+  let mut quantizer = ColorQuantizer::new(ColorSupport::Ansi16);
+  assert_eq!(quantizer.quantize(Rgb(250, 10, 10)), AnsiColor::Ansi16(9));
+  assert_eq!(quantizer.quantize(Rgb(190, 20, 20)), AnsiColor::Ansi16(1));
+  assert_eq!(quantizer.quantize(Rgb(30, 30, 30)), AnsiColor::Ansi16(0));
+  assert_eq!(quantizer.quantize(Rgb(250, 250, 250)), AnsiColor::Ansi16(15));
+  assert_eq!(quantizer.quantize(Rgb(70, 70, 250)), AnsiColor::Ansi16(12));
+}
+
+#[test]
+fn test_quantize_truecolor_and_cache() {
+  let mut quantizer = ColorQuantizer::new(ColorSupport::TrueColor);
+  assert_eq!(
+    quantizer.quantize(Rgb(1, 2, 3)),
+    AnsiColor::Rgb(Rgb(1, 2, 3))
+  );
+  assert_eq!(quantizer.get_cache_len(), 0);
+
+  let mut quantizer = ColorQuantizer::new(ColorSupport::Ansi256);
+  quantizer.quantize(Rgb(255, 165, 0));
+  quantizer.quantize(Rgb(255, 165, 0));
+  assert_eq!(quantizer.get_cache_len(), 1);
+  quantizer.quantize(Rgb(0, 128, 255));
+  assert_eq!(quantizer.get_cache_len(), 2);
+}
+
+#[test]
+fn test_fg_escape_codes() {
+  assert_eq!(
+    AnsiColor::Rgb(Rgb(255, 165, 0)).fg_escape_code(),
+    "\x1b[38;2;255;165;0m"
+  );
+  assert_eq!(AnsiColor::Ansi256(214).fg_escape_code(), "\x1b[38;5;214m");
+  assert_eq!(AnsiColor::Ansi16(1).fg_escape_code(), "\x1b[31m");
+  assert_eq!(AnsiColor::Ansi16(9).fg_escape_code(), "\x1b[91m");
+}
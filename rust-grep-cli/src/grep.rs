@@ -2,12 +2,15 @@ use std::{error::Error, fs};
 
 use r3bl_rs_utils::utils::{style_primary, style_prompt};
 
-use crate::grep_command_builder::GrepOptions;
+use crate::{color_policy::ColorPolicy, grep_command_builder::GrepOptions};
 
-pub fn grep(options: GrepOptions) -> Result<(), Box<dyn Error>> {
+pub fn grep(
+  options: GrepOptions,
+  color_policy: ColorPolicy,
+) -> Result<(), Box<dyn Error>> {
   println!(
     "{}: search for '{}' in '{}' w/ {}",
-    style_prompt("DEBUG"),
+    color_policy.paint(style_prompt("DEBUG")),
     options.search,
     options.file_path,
     match options.case_sensitive {
@@ -28,7 +31,7 @@ pub fn grep(options: GrepOptions) -> Result<(), Box<dyn Error>> {
     })
     .map(|line| {
       let from = &options.search;
-      let to = format!("{}", style_primary(&options.search));
+      let to = color_policy.paint(style_primary(&options.search));
       line.replace(from, &to)
     })
     .collect::<Vec<String>>();
@@ -1,10 +1,14 @@
 // Connect to Rust source files.
+mod color_policy;
+#[allow(dead_code)] // Not used yet, the styles from `r3bl_rs_utils` are all 16 color.
+mod color_quantizer;
 mod grep;
 mod grep_command_builder;
 mod piped_grep;
 mod piped_grep_command_builder;
 
 // Imports.
+use color_policy::ColorPolicy;
 use grep::grep;
 use grep_command_builder::GrepOptionsBuilder;
 use piped_grep::piped_grep;
@@ -27,25 +31,38 @@ use std::process::exit;
 /// Content is piped into the program via `stdin` and it only needs to take the search string and
 /// the case sensitive matching as arguments.
 ///
+/// # Colors
+///
+/// Pass `--no-color` (anywhere in the arguments), set the `NO_COLOR` environment variable,
+/// or redirect `stdout` to disable colorized output. More info in [ColorPolicy].
+///
 /// More info on `Box<dyn Error>` or `&'static dyn Error`:
 /// - `'static` is the lifetime of `Box<dyn Error>`.
 /// - <https://users.rust-lang.org/t/what-does-it-mean-to-return-dyn-error-static/37619/7>
 /// - <https://doc.rust-lang.org/reference/lifetime-elision.html#default-trait-object-lifetimes>
 fn main() {
-  let args = args().collect::<Vec<String>>();
-  with(run(args), |it| match it {
+  let (args, color_policy) =
+    ColorPolicy::extract_from_args(args().collect::<Vec<String>>());
+  with(run(args, color_policy), |it| match it {
     Ok(()) => exit(0),
     Err(err) => {
-      eprintln!("{}: {}", style_error("Problem encountered"), err);
+      eprintln!(
+        "{}: {}",
+        color_policy.paint(style_error("Problem encountered")),
+        err
+      );
       exit(1);
     }
   });
 }
 
-fn run(args: Vec<String>) -> Result<(), Box<dyn Error>> {
+fn run(
+  args: Vec<String>,
+  color_policy: ColorPolicy,
+) -> Result<(), Box<dyn Error>> {
   match is_stdin_piped() {
-    true => piped_grep(PipedGrepOptionsBuilder::parse(args)?)?,
-    false => grep(GrepOptionsBuilder::parse(args)?)?,
+    true => piped_grep(PipedGrepOptionsBuilder::parse(args)?, color_policy)?,
+    false => grep(GrepOptionsBuilder::parse(args)?, color_policy)?,
   }
   Ok(())
 }
@@ -5,12 +5,15 @@ use std::{
 
 use r3bl_rs_utils::utils::{style_primary, style_prompt};
 
-use crate::piped_grep_command_builder::PipedGrepOptions;
+use crate::{color_policy::ColorPolicy, piped_grep_command_builder::PipedGrepOptions};
 
-pub fn piped_grep(options: PipedGrepOptions) -> Result<(), Box<dyn Error>> {
+pub fn piped_grep(
+  options: PipedGrepOptions,
+  color_policy: ColorPolicy,
+) -> Result<(), Box<dyn Error>> {
   println!(
     "{}: search for '{}' in `stdin` w/ {}",
-    style_prompt("DEBUG"),
+    color_policy.paint(style_prompt("DEBUG")),
     options.search,
     match options.case_sensitive {
       true => "case sensitive",
@@ -31,7 +34,7 @@ pub fn piped_grep(options: PipedGrepOptions) -> Result<(), Box<dyn Error>> {
     .map(|line| line.unwrap())
     .for_each(|line| {
       let from = &options.search;
-      let to = format!("{}", style_primary(&options.search));
+      let to = color_policy.paint(style_primary(&options.search));
       let line = line.replace(from, &to);
       println!("{}", line);
     });
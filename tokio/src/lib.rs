@@ -17,3 +17,4 @@
 // Connect to source files.
 pub mod middleware;
 pub mod my_middleware;
+pub mod shared_writer;
@@ -18,15 +18,17 @@
 use tokio_example_lib::{
   middleware::{Future, SafeFnWrapper},
   my_middleware::{adder_mw, logger_mw, Action},
+  shared_writer::SharedWriter,
 };
 
 #[tokio::main]
 async fn main() {
   let mut handles = Vec::<Future<Option<Action>>>::new();
+  let writer = SharedWriter::stdout();
 
   // Spawn tasks and don't await their completion - fire and forget so to speak.
   {
-    let mw_fun: SafeFnWrapper<Action> = logger_mw();
+    let mw_fun: SafeFnWrapper<Action> = logger_mw(writer.clone());
     handles.push(mw_fun.spawn(Action::Add(1, 2)));
     handles.push(mw_fun.spawn(Action::Add(1, 2)));
   }
@@ -34,8 +36,10 @@ async fn main() {
   // Spawn tasks and await their completion.
   {
     let mw_fun: SafeFnWrapper<Action> = adder_mw();
-    println!("{:?}", mw_fun.spawn(Action::Add(1, 2)).await.unwrap());
-    println!("{:?}", mw_fun.spawn(Action::Add(1, 2)).await.unwrap());
+    let result = mw_fun.spawn(Action::Add(1, 2)).await.unwrap();
+    writer.write_line(format!("{:?}", result)).unwrap();
+    let result = mw_fun.spawn(Action::Add(1, 2)).await.unwrap();
+    writer.write_line(format!("{:?}", result)).unwrap();
   }
 
   // Needed to wait for all the spawned futures to complete, otherwise the tokio runtime spawned in
@@ -17,10 +17,16 @@
 // Imports.
 use rand::Rng;
 use std::{
+  cmp::Reverse,
+  collections::BinaryHeap,
   marker::{Send, Sync},
-  sync::Arc,
+  ops::Range,
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+  },
 };
-use tokio::{sync::RwLock, task::JoinHandle};
+use tokio::{sync::RwLock, task::JoinHandle, time::Duration};
 
 /// Excellent resources on lifetimes, closures, and returning references:
 /// 1. https://stackoverflow.com/questions/59442080/rust-pass-a-function-reference-to-threads
@@ -32,8 +38,44 @@ pub type SafeFn<A> = Arc<RwLock<dyn FnMut(A) -> Option<A> + Sync + Send>>;
 //                   Safe to pass      Declare`FnMut` has thread safety
 //                   around.           requirement to rust compiler.
 
+/// How long [SafeFnWrapper::spawn] waits before calling the function. Tests can use a
+/// [Delay::Fixed] delay along w/ a paused tokio clock (`#[tokio::test(start_paused = true)]`) to
+/// get a deterministic order of execution, w/out any real sleeps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Delay {
+  /// Random delay (in ms) picked from the given range.
+  RandomMs(Range<u64>),
+  Fixed(Duration),
+}
+
+impl Default for Delay {
+  fn default() -> Self {
+    Delay::RandomMs(100..1_000)
+  }
+}
+
+impl Delay {
+  /// An empty [Delay::RandomMs] range (eg: `5..5`) is treated as a fixed delay of
+  /// `range.start` ms, rather than panicking inside the spawned task.
+  pub fn get_duration(&self) -> Duration {
+    match self {
+      Delay::RandomMs(range) if range.is_empty() => Duration::from_millis(range.start),
+      Delay::RandomMs(range) => {
+        Duration::from_millis(rand::thread_rng().gen_range(range.clone()))
+      }
+      Delay::Fixed(duration) => *duration,
+    }
+  }
+}
+
 pub struct SafeFnWrapper<A> {
   fn_mut: SafeFn<A>,
+  delay: Delay,
+  /// Used by [dispatch_by_priority]. Lower values run first.
+  priority: u32,
+  /// Incremented by each call to [SafeFnWrapper::dispatch_debounced], so a pending action can
+  /// tell if a newer one has been dispatched since.
+  debounce_generation: Arc<AtomicU64>,
 }
 
 pub type Future<T> = JoinHandle<T>;
@@ -46,7 +88,32 @@ impl<A: Sync + Send + 'static> SafeFnWrapper<A> {
   }
 
   pub fn set(fn_mut: SafeFn<A>) -> Self {
-    Self { fn_mut }
+    Self {
+      fn_mut,
+      delay: Delay::default(),
+      priority: 0,
+      debounce_generation: Arc::new(AtomicU64::new(0)),
+    }
+  }
+
+  pub fn with_delay(
+    mut self,
+    delay: Delay,
+  ) -> Self {
+    self.delay = delay;
+    self
+  }
+
+  pub fn with_priority(
+    mut self,
+    priority: u32,
+  ) -> Self {
+    self.priority = priority;
+    self
+  }
+
+  pub fn get_priority(&self) -> u32 {
+    self.priority
   }
 
   /// Get a clone of the `fn_mut` field (which holds a thread safe `FnMut`).
@@ -58,14 +125,72 @@ impl<A: Sync + Send + 'static> SafeFnWrapper<A> {
   pub fn spawn(
     &self,
     action: A,
+  ) -> Future<Option<A>> {
+    self.dispatch_delayed(action, self.delay.get_duration())
+  }
+
+  /// Calls the function after `duration` (instead of after [Delay]). A task can use this to
+  /// schedule itself again, eg: a poller that dispatches an action every few seconds.
+  pub fn dispatch_delayed(
+    &self,
+    action: A,
+    duration: Duration,
+  ) -> Future<Option<A>> {
+    let arc_lock_fn_mut = self.get();
+    tokio::spawn(async move {
+      tokio::time::sleep(duration).await;
+      let mut fn_mut = arc_lock_fn_mut.write().await;
+      fn_mut(action)
+    })
+  }
+
+  /// Calls the function after `duration`, unless this is called again before then, in which
+  /// case the pending action is dropped & its future resolves to `None` (cancel on newer).
+  /// Eg: a search box can dispatch on every keystroke, and only the last one is run once the
+  /// user stops typing.
+  pub fn dispatch_debounced(
+    &self,
+    action: A,
+    duration: Duration,
   ) -> Future<Option<A>> {
     let arc_lock_fn_mut = self.get();
+    let generation = self.debounce_generation.fetch_add(1, Ordering::SeqCst) + 1;
+    let latest_generation = self.debounce_generation.clone();
     tokio::spawn(async move {
-      // Delay before calling the function.
-      let delay_ms = rand::thread_rng().gen_range(100..1_000) as u64;
-      tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
+      tokio::time::sleep(duration).await;
+      if latest_generation.load(Ordering::SeqCst) != generation {
+        return None;
+      }
       let mut fn_mut = arc_lock_fn_mut.write().await;
       fn_mut(action)
     })
   }
 }
+
+/// Calls each of the `middlewares` w/ a clone of `action`, one after another, in priority order
+/// (lower values run first, and middlewares w/ the same priority run in the order they are
+/// given). There is no [Delay], since the order is set by the priorities. The future resolves to
+/// the return values, in the order that the middlewares ran.
+pub fn dispatch_by_priority<A: Clone + Sync + Send + 'static>(
+  middlewares: &[&SafeFnWrapper<A>],
+  action: A,
+) -> Future<Vec<Option<A>>> {
+  // The index breaks ties between middlewares w/ the same priority.
+  let mut queue = middlewares
+    .iter()
+    .enumerate()
+    .map(|(index, middleware)| Reverse((middleware.get_priority(), index)))
+    .collect::<BinaryHeap<Reverse<(u32, usize)>>>();
+  let mut fn_muts = vec![];
+  while let Some(Reverse((_, index))) = queue.pop() {
+    fn_muts.push(middlewares[index].get());
+  }
+  tokio::spawn(async move {
+    let mut results = vec![];
+    for arc_lock_fn_mut in fn_muts {
+      let mut fn_mut = arc_lock_fn_mut.write().await;
+      results.push(fn_mut(action.clone()));
+    }
+    results
+  })
+}
@@ -14,12 +14,12 @@
  limitations under the License.
 */
 
-use crate::middleware::SafeFnWrapper;
+use crate::{middleware::SafeFnWrapper, shared_writer::SharedWriter};
 
-/// Does not capture context or return anything.
-pub fn logger_mw() -> SafeFnWrapper<Action> {
-  let logger_lambda = |action: Action| {
-    println!("logging: {:?}", action);
+/// Captures the `writer` (to log to) and does not return anything.
+pub fn logger_mw(writer: SharedWriter) -> SafeFnWrapper<Action> {
+  let logger_lambda = move |action: Action| {
+    writer.write_line(format!("logging: {:?}", action)).unwrap();
     None
   };
   SafeFnWrapper::new(logger_lambda)
@@ -0,0 +1,101 @@
+/*
+ Copyright 2022 Nazmul Idris
+
+ Licensed under the Apache License, Version 2.0 (the "License");
+ you may not use this file except in compliance with the License.
+ You may obtain a copy of the License at
+
+      https://www.apache.org/licenses/LICENSE-2.0
+
+ Unless required by applicable law or agreed to in writing, software
+ distributed under the License is distributed on an "AS IS" BASIS,
+ WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ See the License for the specific language governing permissions and
+ limitations under the License.
+*/
+
+// Imports.
+use std::{
+  fmt::Display,
+  fs::File,
+  io::{self, Write},
+  path::Path,
+  sync::{Arc, Mutex},
+};
+
+/// Where the output of a [SharedWriter] ends up.
+pub enum OutputDevice {
+  Stdout,
+  File(File),
+  /// Useful for tests, the contents can be read back via
+  /// [SharedWriter::get_buffer_contents].
+  Buffer(Vec<u8>),
+}
+
+impl Write for OutputDevice {
+  fn write(
+    &mut self,
+    buf: &[u8],
+  ) -> io::Result<usize> {
+    match self {
+      OutputDevice::Stdout => io::stdout().write(buf),
+      OutputDevice::File(file) => file.write(buf),
+      OutputDevice::Buffer(buffer) => buffer.write(buf),
+    }
+  }
+
+  fn flush(&mut self) -> io::Result<()> {
+    match self {
+      OutputDevice::Stdout => io::stdout().flush(),
+      OutputDevice::File(file) => file.flush(),
+      OutputDevice::Buffer(_) => Ok(()),
+    }
+  }
+}
+
+/// Use this instead of `println!` in middlewares (and any other code that runs in spawned
+/// tasks). All the clones of a [SharedWriter] write to the same [OutputDevice], and each
+/// line is written while holding the lock, so the output of tasks running in parallel is
+/// serialized instead of being interleaved.
+#[derive(Clone)]
+pub struct SharedWriter {
+  device: Arc<Mutex<OutputDevice>>,
+}
+
+impl SharedWriter {
+  pub fn new(device: OutputDevice) -> Self {
+    Self {
+      device: Arc::new(Mutex::new(device)),
+    }
+  }
+
+  pub fn stdout() -> Self {
+    SharedWriter::new(OutputDevice::Stdout)
+  }
+
+  pub fn buffer() -> Self {
+    SharedWriter::new(OutputDevice::Buffer(Vec::new()))
+  }
+
+  pub fn file(path: impl AsRef<Path>) -> io::Result<Self> {
+    Ok(SharedWriter::new(OutputDevice::File(File::create(path)?)))
+  }
+
+  /// Writes `line` followed by a newline to the underlying [OutputDevice].
+  pub fn write_line(
+    &self,
+    line: impl Display,
+  ) -> io::Result<()> {
+    let mut device = self.device.lock().unwrap();
+    writeln!(device, "{}", line)?;
+    device.flush()
+  }
+
+  /// Returns `None` if the underlying [OutputDevice] is not a [OutputDevice::Buffer].
+  pub fn get_buffer_contents(&self) -> Option<String> {
+    match &*self.device.lock().unwrap() {
+      OutputDevice::Buffer(buffer) => Some(String::from_utf8_lossy(buffer).to_string()),
+      _ => None,
+    }
+  }
+}
@@ -19,6 +19,7 @@ use std::sync::{Arc, Mutex};
 use tokio_example_lib::{
   middleware::SafeFnWrapper,
   my_middleware::{adder_mw, logger_mw, Action},
+  shared_writer::SharedWriter,
 };
 
 // Integration tests
@@ -28,8 +29,38 @@ use tokio_example_lib::{
 
 #[tokio::test]
 async fn test_logger_mw_works() {
-  let result = logger_mw().spawn(Action::Add(1, 2)).await.unwrap();
+  let writer = SharedWriter::buffer();
+  let result = logger_mw(writer.clone())
+    .spawn(Action::Add(1, 2))
+    .await
+    .unwrap();
   assert!(result.is_none());
+  assert_eq!(
+    writer.get_buffer_contents().unwrap(),
+    "logging: Add(1, 2)\n"
+  );
+}
+
+#[tokio::test]
+async fn test_shared_writer_does_not_interleave_lines() {
+  let writer = SharedWriter::buffer();
+  let mw_fun = logger_mw(writer.clone());
+  let handles = (0..10)
+    .map(|it| mw_fun.spawn(Action::Add(it, it)))
+    .collect::<Vec<_>>();
+  for handle in handles {
+    handle.await.unwrap();
+  }
+  let contents = writer.get_buffer_contents().unwrap();
+  assert_eq!(contents.lines().count(), 10);
+  assert!(contents
+    .lines()
+    .all(|line| line.starts_with("logging: Add(")));
+}
+
+#[test]
+fn test_shared_writer_stdout_has_no_buffer() {
+  assert!(SharedWriter::stdout().get_buffer_contents().is_none());
 }
 
 #[tokio::test]
@@ -0,0 +1,233 @@
+/*
+ Copyright 2022 Nazmul Idris
+
+ Licensed under the Apache License, Version 2.0 (the "License");
+ you may not use this file except in compliance with the License.
+ You may obtain a copy of the License at
+
+      https://www.apache.org/licenses/LICENSE-2.0
+
+ Unless required by applicable law or agreed to in writing, software
+ distributed under the License is distributed on an "AS IS" BASIS,
+ WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ See the License for the specific language governing permissions and
+ limitations under the License.
+*/
+
+use std::sync::{Arc, Mutex};
+
+use tokio::time::{timeout, Duration, Instant};
+use tokio_example_lib::{
+  middleware::{dispatch_by_priority, Delay, SafeFnWrapper},
+  my_middleware::Action,
+};
+
+// Integration tests that run w/ a paused tokio clock. When all the tasks are idle (eg: sleeping)
+// the runtime advances the clock to the next timer instantly, so sleeps don't take real time and
+// the order in which middlewares run is deterministic.
+// Paused clock: <https://docs.rs/tokio/latest/tokio/time/fn.pause.html>
+
+/// Records each action that a middleware is called with, along w/ the (virtual) time elapsed
+/// since the log was created.
+#[derive(Clone)]
+struct DispatchLog {
+  start: Instant,
+  entries: Arc<Mutex<Vec<(Duration, Action)>>>,
+}
+
+impl DispatchLog {
+  fn new() -> Self {
+    Self {
+      start: Instant::now(),
+      entries: Arc::new(Mutex::new(vec![])),
+    }
+  }
+
+  /// Returns a middleware that records the action it is called with, then returns `None`.
+  fn recording_mw(
+    &self,
+    delay: Delay,
+  ) -> SafeFnWrapper<Action> {
+    let log = self.clone();
+    SafeFnWrapper::new(move |action: Action| {
+      log
+        .entries
+        .lock()
+        .unwrap()
+        .push((log.start.elapsed(), action));
+      None
+    })
+    .with_delay(delay)
+  }
+
+  fn get_entries(&self) -> Vec<(Duration, Action)> {
+    self.entries.lock().unwrap().clone()
+  }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_middlewares_run_in_delay_order() {
+  let log = DispatchLog::new();
+  let slow_mw = log.recording_mw(Delay::Fixed(Duration::from_millis(300)));
+  let fast_mw = log.recording_mw(Delay::Fixed(Duration::from_millis(100)));
+
+  let handles = vec![
+    slow_mw.spawn(Action::Add(1, 1)),
+    fast_mw.spawn(Action::Add(2, 2)),
+    slow_mw.spawn(Action::Add(3, 3)),
+  ];
+  for handle in handles {
+    handle.await.unwrap();
+  }
+
+  let entries = log.get_entries();
+  assert_eq!(entries.len(), 3);
+  assert_eq!(entries[0], (Duration::from_millis(100), Action::Add(2, 2)));
+  assert_eq!(entries[1].0, Duration::from_millis(300));
+  assert_eq!(entries[2].0, Duration::from_millis(300));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_middleware_timeout_without_real_sleep() {
+  let log = DispatchLog::new();
+  let slow_mw = log.recording_mw(Delay::Fixed(Duration::from_secs(60)));
+
+  let result = timeout(Duration::from_millis(50), slow_mw.spawn(Action::Add(1, 2))).await;
+  assert!(result.is_err());
+  assert_eq!(log.start.elapsed(), Duration::from_millis(50));
+  assert!(log.get_entries().is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_default_delay_is_within_range() {
+  let log = DispatchLog::new();
+  let mw = log.recording_mw(Delay::default());
+  mw.spawn(Action::Add(1, 2)).await.unwrap();
+
+  let (elapsed, action) = log.get_entries()[0].clone();
+  assert_eq!(action, Action::Add(1, 2));
+  assert!(elapsed >= Duration::from_millis(100));
+  assert!(elapsed < Duration::from_millis(1_000));
+}
+
+#[allow(clippy::reversed_empty_ranges)]
+#[tokio::test(start_paused = true)]
+async fn test_empty_random_delay_range_falls_back_to_start() {
+  assert_eq!(Delay::RandomMs(5..5).get_duration(), Duration::from_millis(5));
+  assert_eq!(Delay::RandomMs(9..3).get_duration(), Duration::from_millis(9));
+
+  let log = DispatchLog::new();
+  let mw = log.recording_mw(Delay::RandomMs(250..250));
+  mw.spawn(Action::Add(1, 2)).await.unwrap();
+  assert_eq!(
+    log.get_entries(),
+    vec![(Duration::from_millis(250), Action::Add(1, 2))]
+  );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_dispatch_delayed_ignores_default_delay() {
+  let log = DispatchLog::new();
+  let mw = log.recording_mw(Delay::default());
+  mw.dispatch_delayed(Action::Add(1, 2), Duration::from_millis(5_000))
+    .await
+    .unwrap();
+
+  assert_eq!(
+    log.get_entries(),
+    vec![(Duration::from_millis(5_000), Action::Add(1, 2))]
+  );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_dispatch_delayed_self_scheduling_poller() {
+  let log = DispatchLog::new();
+  let mw = log.recording_mw(Delay::default());
+  for count in 0..3 {
+    mw.dispatch_delayed(Action::Result(count), Duration::from_secs(1))
+      .await
+      .unwrap();
+  }
+
+  assert_eq!(
+    log.get_entries(),
+    vec![
+      (Duration::from_secs(1), Action::Result(0)),
+      (Duration::from_secs(2), Action::Result(1)),
+      (Duration::from_secs(3), Action::Result(2)),
+    ]
+  );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_dispatch_debounced_cancels_on_newer() {
+  let start = Instant::now();
+  let adder = SafeFnWrapper::new(|action: Action| match action {
+    Action::Add(a, b) => Some(Action::Result(a + b)),
+    _ => None,
+  });
+  let debounce = Duration::from_millis(200);
+
+  // Keystrokes 100ms apart (less than the debounce duration), so only the last one runs.
+  let first = adder.dispatch_debounced(Action::Add(1, 0), debounce);
+  tokio::time::sleep(Duration::from_millis(100)).await;
+  let second = adder.dispatch_debounced(Action::Add(2, 0), debounce);
+  tokio::time::sleep(Duration::from_millis(100)).await;
+  let third = adder.dispatch_debounced(Action::Add(3, 0), debounce);
+
+  assert_eq!(first.await.unwrap(), None);
+  assert_eq!(second.await.unwrap(), None);
+  assert_eq!(third.await.unwrap(), Some(Action::Result(3)));
+  assert_eq!(start.elapsed(), Duration::from_millis(400));
+
+  // Once the debounce duration has passed, the next dispatch runs again.
+  let log = DispatchLog::new();
+  let mw = log.recording_mw(Delay::default());
+  mw.dispatch_debounced(Action::Add(1, 1), debounce)
+    .await
+    .unwrap();
+  mw.dispatch_debounced(Action::Add(2, 2), debounce)
+    .await
+    .unwrap();
+  assert_eq!(
+    log.get_entries(),
+    vec![
+      (Duration::from_millis(200), Action::Add(1, 1)),
+      (Duration::from_millis(400), Action::Add(2, 2)),
+    ]
+  );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_dispatch_by_priority_runs_lower_values_first() {
+  let names = Arc::new(Mutex::new(Vec::<&str>::new()));
+  let named_mw = |name: &'static str, priority: u32| {
+    let names = names.clone();
+    SafeFnWrapper::new(move |action: Action| {
+      names.lock().unwrap().push(name);
+      match action {
+        Action::Add(a, b) => Some(Action::Result(a + b)),
+        _ => None,
+      }
+    })
+    .with_priority(priority)
+  };
+  let logger = named_mw("logger", 10);
+  let validator = named_mw("validator", 0);
+  let adder = named_mw("adder", 5);
+  let second_adder = named_mw("second adder", 5);
+
+  let results = dispatch_by_priority(
+    &[&logger, &adder, &validator, &second_adder],
+    Action::Add(1, 2),
+  )
+  .await
+  .unwrap();
+
+  // Ties run in the order the middlewares were given.
+  assert_eq!(
+    *names.lock().unwrap(),
+    vec!["validator", "adder", "second adder", "logger"]
+  );
+  assert_eq!(results, vec![Some(Action::Result(3)); 4]);
+}